@@ -2,12 +2,18 @@
 
 use structopt::StructOpt;
 
-use super::global::{LabelArgs, MetaArgs, RegexArgs, StyleArgs};
+use super::global::{GraphArgs, LabelArgs, MetaArgs, RegexArgs, StyleArgs};
+use crate::{
+    git::{markers::StatusFilter, owners::OwnerKey},
+    traverse::{filters::Timestamp, modes::SortMode},
+    utils::export::ExportFormat,
+};
 
 #[derive(Debug, PartialEq, StructOpt)]
 pub enum GitOptions {
     /// The `git add` command.
     /// This may be used after running nomad in a labeled mode.
+    /// Use `-p` to interactively choose hunks to stage.
     Add(AddOptions),
     /// The `git blame` command.
     /// This may be used after running nomad in a labeled mode.
@@ -16,17 +22,29 @@ pub enum GitOptions {
     /// The `git branch` command. Displays branches in tree form by default (this behavior may be
     /// disabled).
     Branch(BranchOptions),
+    /// Display how often each file and directory changed in tree form. Items are colored
+    /// from cool to hot by the number of commits that touched them.
+    Churn(ChurnOptions),
     /// The `git commit` command.
     /// Optionally include a message after the command, ie. `git commit "YOUR MESSAGE HERE"`
-    /// The default commit message is "Updating" if no message is included.
-    Commit { message: Option<String> },
+    /// Your $EDITOR is opened to write the message if no message is included.
+    Commit(CommitOptions),
     /// The `git diff` command.
     /// This may be used after running nomad in a labeled mode.
-    Diff { item_labels: Vec<String> },
+    Diff(DiffOptions),
+    /// Display the authors that own the most lines in each file and directory, based on
+    /// the Git blame of every file at `HEAD`.
+    Owners(OwnersOptions),
     /// The `git restore` command. This may be used after running nomad in a labeled mode.
     Restore(RestoreOptions),
+    /// The `git reset` command. Unstages files by restoring their index entries to `HEAD`.
+    /// This may be used after running nomad in a labeled mode.
+    Reset(ResetOptions),
     /// The `git status` command. Only display changed/unstaged files in the tree.
     Status(StatusOptions),
+    /// The `git switch` command. Switch to a branch by its number after running
+    /// `git branch -n`, or by its name.
+    Switch(SwitchOptions),
 }
 
 #[derive(Debug, PartialEq, StructOpt)]
@@ -40,6 +58,14 @@ pub struct AddOptions {
         help = "Add changes from all tracked and untracked files"
     )]
     pub all: bool,
+
+    #[structopt(
+        short = "p",
+        long = "patch",
+        conflicts_with = "all",
+        help = "Interactively choose hunks of the working tree diff to add"
+    )]
+    pub patch: bool,
 }
 
 #[derive(Debug, PartialEq, StructOpt)]
@@ -63,15 +89,72 @@ pub struct BlameOptions {
 
 #[derive(Debug, PartialEq, StructOpt)]
 pub struct BranchOptions {
+    #[structopt(
+        short = "a",
+        long = "all",
+        help = "Display remote-tracking branches under their remotes alongside local branches"
+    )]
+    pub all: bool,
+
+    #[structopt(
+        short = "c",
+        long = "create",
+        conflicts_with_all = &["delete", "rename"],
+        help = "Create a new branch pointing to HEAD or to the revision passed into --from"
+    )]
+    pub create: Option<String>,
+
+    #[structopt(
+        short = "d",
+        long = "delete",
+        conflicts_with = "rename",
+        help = "Delete the branches matching these numbers or names"
+    )]
+    pub delete: Vec<String>,
+
     #[structopt(
         long = "export",
         help = "Export the tree to a file. Optionally include a target filename"
     )]
     pub export: Option<Option<String>>,
 
+    #[structopt(
+        long = "export-format",
+        default_value = "txt",
+        possible_values = &["html", "md", "txt"],
+        help = "The format of the exported tree"
+    )]
+    pub export_format: ExportFormat,
+
     #[structopt(short, long, help = "Display branches in a normal list")]
     pub flat: bool,
 
+    #[structopt(
+        long = "force",
+        help = "Delete branches even if they are not merged into HEAD"
+    )]
+    pub force: bool,
+
+    #[structopt(
+        long = "from",
+        requires = "create",
+        help = "The revision the branch created with --create points to"
+    )]
+    pub from: Option<String>,
+
+    #[structopt(
+        long = "merged",
+        conflicts_with = "no-merged",
+        help = "Only display branches merged into this revision. Defaults to HEAD"
+    )]
+    pub merged: Option<Option<String>>,
+
+    #[structopt(
+        long = "no-merged",
+        help = "Only display branches not merged into this revision. Defaults to HEAD"
+    )]
+    pub no_merged: Option<Option<String>>,
+
     #[structopt(short = "n", long = "numbered", help = "Label branches with numbers")]
     pub numbers: bool,
 
@@ -82,6 +165,14 @@ pub struct BranchOptions {
     )]
     pub pattern: Option<String>,
 
+    #[structopt(
+        long = "rename",
+        number_of_values = 2,
+        value_names = &["BRANCH", "NEW_NAME"],
+        help = "Rename the branch matching this number or name"
+    )]
+    pub rename: Vec<String>,
+
     #[structopt(short, long, help = "Display the total number of branches")]
     pub statistics: bool,
 
@@ -89,12 +180,211 @@ pub struct BranchOptions {
     pub no_icons: bool,
 }
 
+#[derive(Debug, PartialEq, StructOpt)]
+pub struct ChurnOptions {
+    #[structopt(
+        long = "export",
+        help = "Export the tree to a file. Optionally include a target filename"
+    )]
+    pub export: Option<Option<String>>,
+
+    #[structopt(
+        long = "export-format",
+        default_value = "txt",
+        possible_values = &["dot", "html", "json", "md", "mermaid", "txt", "xml"],
+        help = "The format of the exported tree"
+    )]
+    pub export_format: ExportFormat,
+
+    #[structopt(flatten)]
+    pub graph: GraphArgs,
+
+    #[structopt(
+        long = "json",
+        help = "Display the tree as a JSON document instead. Labels and numbers are always included"
+    )]
+    pub json: bool,
+
+    #[structopt(flatten)]
+    pub labels: LabelArgs,
+
+    #[structopt(
+        short = "p",
+        long = "pattern",
+        help = "Only display items matching this pattern. Supports regex expressions"
+    )]
+    pub pattern: Option<String>,
+
+    #[structopt(long = "reverse", help = "Reverse the sort order")]
+    pub reverse: bool,
+
+    #[structopt(
+        long = "since",
+        help = "Only count commits made after this time. Accepts relative times (ie. `90d`) or dates (ie. `2026-01-01`)"
+    )]
+    pub since: Option<Timestamp>,
+
+    #[structopt(
+        long = "sort",
+        possible_values = &["churn", "extension", "name", "natural"],
+        help = "Sort the items within each directory. `churn` displays the items touched by the most commits first"
+    )]
+    pub sort: Option<SortMode>,
+
+    #[structopt(
+        short = "s",
+        long = "stats",
+        help = "Display traversal statistics after the tree is displayed"
+    )]
+    pub statistics: bool,
+
+    #[structopt(flatten)]
+    pub style: StyleArgs,
+}
+
+#[derive(Debug, PartialEq, StructOpt)]
+pub struct CommitOptions {
+    #[structopt(help = "The commit message")]
+    pub message: Option<String>,
+
+    #[structopt(
+        long = "allow-empty",
+        help = "Allow a commit that does not contain any staged changes"
+    )]
+    pub allow_empty: bool,
+
+    #[structopt(
+        long = "amend",
+        help = "Replace the last commit with a new commit containing its changes and the staged changes"
+    )]
+    pub amend: bool,
+
+    #[structopt(
+        short = "s",
+        long = "signoff",
+        help = "Add a Signed-off-by trailer for the committer to the end of the message"
+    )]
+    pub signoff: bool,
+}
+
+#[derive(Debug, PartialEq, StructOpt)]
+pub struct DiffOptions {
+    #[structopt(help = "The item labels to diff")]
+    pub item_labels: Vec<String>,
+
+    #[structopt(flatten)]
+    pub labels: LabelArgs,
+
+    #[structopt(
+        long = "staged",
+        visible_alias = "cached",
+        help = "Compare the index instead of the working directory with `HEAD` or the revision passed into `--tree`"
+    )]
+    pub staged: bool,
+
+    #[structopt(flatten)]
+    pub style: StyleArgs,
+
+    #[structopt(
+        long = "tree",
+        help = "Display the changed files in tree form. Optionally include a revision or a range of revisions, ie. `main..feature`"
+    )]
+    pub tree: Option<Option<String>>,
+}
+
+#[derive(Debug, PartialEq, StructOpt)]
+pub struct OwnersOptions {
+    #[structopt(
+        long = "by",
+        default_value = "author",
+        possible_values = &["author", "email"],
+        help = "Identify the owner of each line by the author's name or email"
+    )]
+    pub by: OwnerKey,
+
+    #[structopt(
+        long = "export",
+        help = "Export the tree to a file. Optionally include a target filename"
+    )]
+    pub export: Option<Option<String>>,
+
+    #[structopt(
+        long = "export-format",
+        default_value = "txt",
+        possible_values = &["dot", "html", "json", "md", "mermaid", "txt", "xml"],
+        help = "The format of the exported tree"
+    )]
+    pub export_format: ExportFormat,
+
+    #[structopt(flatten)]
+    pub graph: GraphArgs,
+
+    #[structopt(
+        long = "json",
+        help = "Display the tree as a JSON document instead. Labels and numbers are always included"
+    )]
+    pub json: bool,
+
+    #[structopt(flatten)]
+    pub labels: LabelArgs,
+
+    #[structopt(
+        short = "p",
+        long = "pattern",
+        help = "Only display items matching this pattern. Supports regex expressions"
+    )]
+    pub pattern: Option<String>,
+
+    #[structopt(
+        long = "since",
+        help = "Only count lines that were committed after this time. Accepts relative times (ie. `90d`) or dates (ie. `2026-01-01`)"
+    )]
+    pub since: Option<Timestamp>,
+
+    #[structopt(
+        short = "s",
+        long = "stats",
+        help = "Display traversal statistics after the tree is displayed"
+    )]
+    pub statistics: bool,
+
+    #[structopt(flatten)]
+    pub style: StyleArgs,
+
+    #[structopt(
+        long = "top",
+        default_value = "3",
+        help = "The number of owners displayed for each item"
+    )]
+    pub top: usize,
+}
+
 #[derive(Debug, PartialEq, StructOpt)]
 pub struct RestoreOptions {
     #[structopt(
         help = "Restore these items to its clean Git state. Restores in the working tree by default"
     )]
     pub item_labels: Vec<String>,
+
+    #[structopt(
+        short = "s",
+        long = "source",
+        help = "Restore the items from this revision instead of HEAD"
+    )]
+    pub source: Option<String>,
+
+    #[structopt(
+        short = "S",
+        long = "staged",
+        help = "Restore the items in the index instead of the working tree (unstage the items)"
+    )]
+    pub staged: bool,
+}
+
+#[derive(Debug, PartialEq, StructOpt)]
+pub struct ResetOptions {
+    #[structopt(help = "Unstage these items")]
+    pub item_labels: Vec<String>,
 }
 
 #[derive(Debug, PartialEq, StructOpt)]
@@ -105,15 +395,53 @@ pub struct StatusOptions {
     )]
     pub export: Option<Option<String>>,
 
+    #[structopt(
+        long = "export-format",
+        default_value = "txt",
+        possible_values = &["dot", "html", "json", "md", "mermaid", "txt", "xml"],
+        help = "The format of the exported tree"
+    )]
+    pub export_format: ExportFormat,
+
+    #[structopt(flatten)]
+    pub graph: GraphArgs,
+
+    #[structopt(
+        long = "json",
+        help = "Display the tree as a JSON document instead. Labels and numbers are always included"
+    )]
+    pub json: bool,
+
     #[structopt(flatten)]
     pub labels: LabelArgs,
 
     #[structopt(flatten)]
     pub meta: MetaArgs,
 
+    #[structopt(
+        long = "numstat",
+        help = "Display the number of added and deleted lines next to each changed file. Directories display the sum of their contents"
+    )]
+    pub numstat: bool,
+
+    #[structopt(
+        long = "only",
+        use_delimiter = true,
+        number_of_values = 1,
+        possible_values = &["conflicted", "deleted", "modified", "staged", "unstaged", "untracked"],
+        help = "Only display files with these Git statuses. Separate multiple statuses with commas"
+    )]
+    pub only: Vec<StatusFilter>,
+
     #[structopt(flatten)]
     pub regex: RegexArgs,
 
+    #[structopt(
+        long = "show-ignored",
+        help = "Display files that are ignored by Git and mark them with the ignored marker"
+    )]
+    pub show_ignored: bool,
+
     #[structopt(
         short = "s",
         long = "stats",
@@ -124,3 +452,16 @@ pub struct StatusOptions {
     #[structopt(flatten)]
     pub style: StyleArgs,
 }
+
+#[derive(Debug, PartialEq, StructOpt)]
+pub struct SwitchOptions {
+    #[structopt(help = "The number or name of the branch to switch to")]
+    pub branch: String,
+
+    #[structopt(
+        short = "f",
+        long = "force",
+        help = "Switch even if the working tree contains changes. The changes are discarded"
+    )]
+    pub force: bool,
+}
@@ -1,5 +1,14 @@
 //! Providing arguments that are used throughout `nomad`.
 
+use crate::{
+    git::markers::StatusFilter,
+    traverse::{
+        filters::{FileSize, SizeRange, Timestamp},
+        modes::SortMode,
+    },
+    utils::export::ExportFormat,
+};
+
 use structopt::StructOpt;
 
 #[derive(Debug, PartialEq, StructOpt)]
@@ -10,6 +19,24 @@ pub struct GlobalArgs {
     )]
     pub export: Option<Option<String>>,
 
+    #[structopt(
+        long = "export-format",
+        default_value = "txt",
+        possible_values = &["dot", "html", "json", "md", "mermaid", "txt", "xml"],
+        help = "The format of the exported tree"
+    )]
+    pub export_format: ExportFormat,
+
+    #[structopt(flatten)]
+    pub graph: GraphArgs,
+
+    #[structopt(
+        long = "json",
+        conflicts_with = "stream",
+        help = "Display the tree as a JSON document instead. Labels and numbers are always included"
+    )]
+    pub json: bool,
+
     #[structopt(flatten)]
     pub labels: LabelArgs,
 
@@ -31,6 +58,27 @@ pub struct GlobalArgs {
         help = "Display traversal statistics after the tree is displayed"
     )]
     pub statistics: bool,
+
+    #[structopt(
+        long = "stream",
+        help = "Display each item as soon as it is visited instead of waiting for the entire tree. Exports still use the full tree"
+    )]
+    pub stream: bool,
+}
+
+#[derive(Debug, PartialEq, StructOpt)]
+pub struct GraphArgs {
+    #[structopt(
+        long = "graph-depth",
+        help = "Only include items up to this depth in `dot` and `mermaid` exports"
+    )]
+    pub graph_depth: Option<usize>,
+
+    #[structopt(
+        long = "graph-max-nodes",
+        help = "Set the maximum number of items to include in `dot` and `mermaid` exports. Defaults to 200"
+    )]
+    pub graph_max_nodes: Option<usize>,
 }
 
 #[derive(Debug, PartialEq, StructOpt)]
@@ -57,8 +105,15 @@ pub struct LabelArgs {
     pub numbers: bool,
 }
 
-#[derive(Debug, PartialEq, StructOpt)]
+#[derive(Debug, Default, PartialEq, StructOpt)]
 pub struct MetaArgs {
+    #[structopt(
+        long = "last-commit",
+        conflicts_with = "stream",
+        help = "Display the short SHA, author, relative date, and subject of the last commit that changed each item"
+    )]
+    pub last_commit: bool,
+
     #[structopt(
         short = "m",
         long = "metadata",
@@ -73,29 +128,151 @@ pub struct MetaArgs {
     pub tokei: bool,
 }
 
-#[derive(Debug, PartialEq, StructOpt)]
+#[derive(Debug, Default, PartialEq, StructOpt)]
 pub struct ModifierArgs {
+    #[structopt(
+        long = "compact-dirs",
+        conflicts_with = "stream",
+        help = "Collapse directories that only contain a single directory into one item, ie. `src/main/java`"
+    )]
+    pub compact_dirs: bool,
+
     #[structopt(long = "dirs", help = "Only display directories")]
     pub dirs: bool,
 
+    #[structopt(
+        long = "dirs-first",
+        conflicts_with = "stream",
+        help = "Display directories before files"
+    )]
+    pub dirs_first: bool,
+
     #[structopt(long = "disrespect", help = "Disrespect all ignore rules")]
     pub disrespect: bool,
 
+    #[structopt(
+        long = "du",
+        conflicts_with = "stream",
+        help = "Display the total apparent and on-disk size of everything within each directory"
+    )]
+    pub du: bool,
+
     #[structopt(long = "hidden", help = "Display hidden files")]
     pub hidden: bool,
 
     #[structopt(long = "max-depth", help = "Set the maximum depth to recurse")]
     pub max_depth: Option<usize>,
 
+    #[structopt(
+        long = "max-items",
+        conflicts_with = "stream",
+        help = "Only display the first N items within each directory. The remaining items are summarized"
+    )]
+    pub max_items: Option<usize>,
+
     #[structopt(
         long = "max-filesize",
         help = "Set the maximum filesize (in bytes) to include in the tree"
     )]
     pub max_filesize: Option<u64>,
+
+    #[structopt(
+        long = "min-size",
+        help = "Only display files that are at least this size, ie. `10M`"
+    )]
+    pub min_size: Option<FileSize>,
+
+    #[structopt(
+        long = "newer",
+        help = "Only display files modified after this time. Accepts relative times (ie. `2d`) or dates (ie. `2026-01-01`)"
+    )]
+    pub newer: Option<Timestamp>,
+
+    #[structopt(
+        long = "newer-than",
+        help = "Only display files modified after this file was last modified"
+    )]
+    pub newer_than: Option<String>,
+
+    #[structopt(
+        long = "older",
+        help = "Only display files modified before this time. Accepts relative times (ie. `2d`) or dates (ie. `2026-01-01`)"
+    )]
+    pub older: Option<Timestamp>,
+
+    #[structopt(
+        long = "only",
+        conflicts_with = "stream",
+        use_delimiter = true,
+        number_of_values = 1,
+        possible_values = &["conflicted", "deleted", "modified", "staged", "unstaged", "untracked"],
+        help = "Only display files with these Git statuses. Separate multiple statuses with commas"
+    )]
+    pub only: Vec<StatusFilter>,
+
+    #[structopt(
+        long = "prune",
+        conflicts_with = "stream",
+        help = "Remove directories that do not contain any items after filtering"
+    )]
+    pub prune: bool,
+
+    #[structopt(
+        long = "rev",
+        conflicts_with_all = &["du", "grep", "min-size", "newer", "newer-than", "older", "only", "show-ignored", "size", "stream", "tokei"],
+        help = "Display the tree at a Git revision instead of the working directory, ie. a branch, a tag, `HEAD~5`, or a SHA"
+    )]
+    pub rev: Option<String>,
+
+    #[structopt(
+        long = "reverse",
+        conflicts_with = "stream",
+        help = "Reverse the sort order"
+    )]
+    pub reverse: bool,
+
+    #[structopt(
+        long = "show-ignored",
+        help = "Display files that are ignored by Git and mark them with the ignored marker"
+    )]
+    pub show_ignored: bool,
+
+    #[structopt(
+        long = "size",
+        help = "Only display files within this size range, ie. `1K..5M`. Either end may be left out"
+    )]
+    pub size: Option<SizeRange>,
+
+    #[structopt(
+        long = "sort",
+        conflicts_with = "stream",
+        possible_values = &["ctime", "extension", "git-status", "loc", "mtime", "name", "natural", "size"],
+        help = "Sort the items within each directory"
+    )]
+    pub sort: Option<SortMode>,
+
+    #[structopt(
+        long = "threads",
+        help = "Set the number of threads used to walk the directory"
+    )]
+    pub threads: Option<usize>,
+
+    #[structopt(
+        long = "top",
+        help = "Set the number of heaviest directories listed in the statistics if `--du` is provided. Defaults to 10"
+    )]
+    pub top: Option<usize>,
 }
 
 #[derive(Debug, PartialEq, StructOpt)]
 pub struct RegexArgs {
+    #[structopt(
+        long = "grep",
+        conflicts_with = "stream",
+        help = "Only display files containing this pattern and list the matching lines. Supports regex expressions"
+    )]
+    pub grep: Option<String>,
+
     #[structopt(
         short = "p",
         long = "pattern",
@@ -18,16 +18,26 @@ pub struct TreeSettings {
     pub git: Option<TreeGit>,
     /// Contains settings for the color of tree labels (items and directories).
     pub labels: Option<LabelColors>,
+    /// Contains the setting for displaying directories before files.
+    pub dirs_first: Option<bool>,
     /// Contains the indentation setting.
     pub indent: Option<usize>,
     /// Contains settings for the tree items' appearance.
     pub items: Option<TreeItems>,
     /// Contains indent characters for the tree itself.
     pub indent_chars: Option<IndentCharacters>,
+    /// Contains the maximum number of items displayed within each directory.
+    pub max_items: Option<usize>,
     /// Contains the padding setting.
     pub padding: Option<usize>,
     /// Contains the setting for the color of the regex match.
     pub regex: Option<Regex>,
+    /// Contains the setting for reversing the sort order.
+    pub reverse: Option<bool>,
+    /// Contains the order in which the items within each directory are sorted.
+    pub sort: Option<String>,
+    /// Contains the number of threads used to walk directories.
+    pub threads: Option<usize>,
 }
 
 /// Contains settings for the TUI.
@@ -118,6 +128,8 @@ pub struct Markers {
     pub conflicted_marker: Option<String>,
     /// The string that marks a deleted file.
     pub deleted_marker: Option<String>,
+    /// The string that marks an ignored file.
+    pub ignored_marker: Option<String>,
     /// The string that marks a modified file.
     pub modified_marker: Option<String>,
     /// The string that marks a renamed file.
@@ -145,6 +157,8 @@ pub struct Colors {
     pub conflicted_color: Option<String>,
     /// The color associated with deleted files.
     pub deleted_color: Option<String>,
+    /// The color associated with ignored files.
+    pub ignored_color: Option<String>,
     /// The color associated with modified files.
     pub modified_color: Option<String>,
     /// The color associated with renamed files.
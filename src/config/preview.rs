@@ -131,6 +131,13 @@ pub fn display_preview_tree(nomad_style: &NomadStyle) -> Result<(), NomadError>
             .untracked_color
             .paint(nomad_style.git.untracked_marker.to_string())
     ));
+    tree.add_empty_child(format!(
+        "{} \u{e74e} ignored file", // ""
+        nomad_style
+            .git
+            .ignored_color
+            .paint(nomad_style.git.ignored_marker.to_string())
+    ));
 
     tree.end_child();
 
@@ -104,6 +104,10 @@ pub enum NomadError {
     #[error("TOML error: {0}")]
     TOMLError(#[from] toml::de::Error),
 
+    /// The tree cannot be exported to DOT, JSON, Mermaid, or XML in this mode.
+    #[error("DOT, JSON, Mermaid, and XML exports are not available for this tree!")]
+    UnsupportedExport,
+
     /// Something went wrong when decoding to UTF-8.
     #[error("UTF-8 error: {0}")]
     UTF8Error(#[from] std::str::Utf8Error),
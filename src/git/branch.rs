@@ -1,31 +1,36 @@
 //! Exposing functionality for the Git branch command.
 
-use std::time::Instant;
+use std::{collections::HashMap, time::Instant};
 
 use crate::{
     cli::{
         git::BranchOptions,
-        global::{GlobalArgs, LabelArgs, MetaArgs, ModifierArgs, RegexArgs, StyleArgs},
+        global::{GlobalArgs, GraphArgs, LabelArgs, MetaArgs, ModifierArgs, RegexArgs, StyleArgs},
     },
     errors::NomadError,
     style::models::NomadStyle,
     traverse::{
-        format::highlight_matched,
+        format::{format_branch_details, highlight_matched},
         models::{DirItem, FoundBranch},
         modes::NomadMode,
         traits::{ToTree, TransformFound},
+        utils::store_branch_numbers,
     },
+    utils::open::get_deserialized_json,
 };
 
 use ansi_term::Colour;
-use anyhow::{Result, __private};
-use git2::{Branch, BranchType, Repository};
+use anyhow::{__private, anyhow, Result};
+use git2::{
+    build::CheckoutBuilder, Branch, BranchType, ObjectType, Oid, Repository, StatusOptions,
+};
 use ptree::{item::StringItem, PrintConfig};
 use regex::Regex;
 
-use super::utils::get_repo_branch;
+use super::utils::{get_ahead_behind, get_repo_branch};
 
-/// Get all local branches from the repository and transform them into a `Vec<FoundBranch>`.
+/// Get the local branches from the repository, as well as the remote-tracking branches if
+/// `--all` was passed, and transform them into a `Vec<FoundBranch>`.
 pub fn display_branches(
     args: &BranchOptions,
     nomad_style: &NomadStyle,
@@ -75,30 +80,61 @@ pub fn display_branches(
     //
     //     https://docs.rs/git2/latest/git2/struct.Branches.html
     //
-    let repo_branches = repo
-        .branches(Some(BranchType::Local))?
+    let mut repo_branches = repo
+        .branches(if args.all {
+            None
+        } else {
+            Some(BranchType::Local)
+        })?
         .filter_map(|repo_branch| {
-            if let Ok((branch, _branch_type)) = repo_branch {
-                Some(branch)
+            if let Ok((branch, branch_type)) = repo_branch {
+                Some((branch, branch_type))
             } else {
                 None
             }
         })
-        .collect::<Vec<Branch>>();
+        .collect::<Vec<(Branch, BranchType)>>();
+
+    // Sorting by the full reference name lists local branches before remote-tracking
+    // branches and keeps branches that share a parent next to each other in the tree.
+    repo_branches.sort_by_cached_key(|(branch, _)| branch.get().name().unwrap_or("?").to_string());
+
+    let merge_filter = match (&args.merged, &args.no_merged) {
+        (Some(base), _) => Some((get_merge_base(base.as_deref(), repo)?, true)),
+        (_, Some(base)) => Some((get_merge_base(base.as_deref(), repo)?, false)),
+        _ => None,
+    };
 
     if args.flat {
         println!();
     }
 
     let mut num_branches = 0;
+    let mut numbered_branches: HashMap<String, String> = HashMap::new();
     let start = Instant::now();
-    for branch in repo_branches {
-        let branch_name = branch.name()?.unwrap_or("?").to_string();
+    for (branch, branch_type) in repo_branches {
+        // Symbolic references such as `origin/HEAD` do not point to a commit.
+        let target = match branch.get().target() {
+            Some(target) => target,
+            None => continue,
+        };
+        if let Some((base, merged)) = merge_filter {
+            if is_merged(target, base, repo)? != merged {
+                continue;
+            }
+        }
+
+        // Remote-tracking branches are grouped under their remote, ie.
+        // `remotes/origin/main`.
+        let branch_name = match branch_type {
+            BranchType::Local => branch.name()?.unwrap_or("?").to_string(),
+            BranchType::Remote => format!("remotes/{}", branch.name()?.unwrap_or("?")),
+        };
 
         let mut is_current_branch = false;
         let marker = match current_branch {
             Some(ref current_branch_name) => {
-                if &branch_name == current_branch_name {
+                if branch_type == BranchType::Local && &branch_name == current_branch_name {
                     is_current_branch = true;
                     Some(format!("{}", Colour::Green.bold().paint("*")))
                 } else {
@@ -123,56 +159,36 @@ pub fn display_branches(
         } else {
             None
         };
+        let ahead_behind = get_ahead_behind(&branch, repo)?;
+        let last_commit_time = Some(repo.find_commit(target)?.time().seconds());
         let number = if args.numbers {
             Some(num_branches)
         } else {
             None
         };
+        numbered_branches.insert(format!("{num_branches}"), branch_name.clone());
 
-        if let Some(ref regex) = regex_expression {
-            if let Some(matched) = regex.find(&branch_name) {
-                if args.flat {
-                    display_flat_branch(
-                        &branch,
-                        &branch_name,
-                        is_current_branch,
-                        marker,
-                        Some((matched.start(), matched.end())),
-                        nomad_style,
-                        number,
-                        upstream,
-                    );
-                } else {
-                    branches.push(FoundBranch {
-                        full_branch: branch_name.clone(),
-                        is_current_branch,
-                        is_head: branch.is_head(),
-                        marker,
-                        matched: Some((matched.start(), matched.end())),
-                        upstream,
-                    });
-                }
-            }
-        } else if args.flat {
-            display_flat_branch(
-                &branch,
-                &branch_name,
-                is_current_branch,
-                marker,
-                None,
-                nomad_style,
-                number,
-                upstream,
-            );
-        } else {
-            branches.push(FoundBranch {
-                full_branch: branch_name.clone(),
+        let matched = regex_expression
+            .as_ref()
+            .and_then(|regex| regex.find(&branch_name))
+            .map(|matched| (matched.start(), matched.end()));
+        if regex_expression.is_none() || matched.is_some() {
+            let found_branch = FoundBranch {
+                ahead_behind,
+                full_branch: branch_name,
                 is_current_branch,
                 is_head: branch.is_head(),
+                last_commit_time,
                 marker,
-                matched: None,
+                matched,
                 upstream,
-            });
+            };
+
+            if args.flat {
+                display_flat_branch(&found_branch, nomad_style, number);
+            } else {
+                branches.push(found_branch);
+            }
         }
 
         num_branches += 1;
@@ -181,6 +197,10 @@ pub fn display_branches(
     if args.flat {
         println!();
 
+        // Branch trees are numbered in tree order, so their numbers are stored when
+        // the tree is built instead.
+        store_branch_numbers(numbered_branches)?;
+
         if args.statistics {
             let duration = start.elapsed().as_millis();
             println!("| {num_branches} branches | {duration} ms |\n");
@@ -190,23 +210,21 @@ pub fn display_branches(
     // Hm... There is probably a better solution, but fuck it. Leaving it for now.
     let global_args = GlobalArgs {
         export: args.export.clone(),
+        export_format: args.export_format,
+        graph: GraphArgs {
+            graph_depth: None,
+            graph_max_nodes: None,
+        },
+        json: false,
         labels: LabelArgs {
             all_labels: false,
             label_directories: false,
             numbers: args.numbers,
         },
-        meta: MetaArgs {
-            metadata: false,
-            tokei: false,
-        },
-        modifiers: ModifierArgs {
-            dirs: false,
-            disrespect: false,
-            hidden: false,
-            max_depth: None,
-            max_filesize: None,
-        },
+        meta: MetaArgs::default(),
+        modifiers: ModifierArgs::default(),
         regex: RegexArgs {
+            grep: None,
             pattern: args.pattern.clone(),
         },
         style: StyleArgs {
@@ -216,6 +234,7 @@ pub fn display_branches(
             plain: false,
         },
         statistics: args.statistics,
+        stream: false,
     };
 
     Ok(if args.flat {
@@ -232,26 +251,17 @@ pub fn display_branches(
 
 /// Format the branch into a flat view and then display it
 /// This is like the standard `git branch` or `git branch --list` commands.
-fn display_flat_branch(
-    branch: &Branch,
-    branch_name: &str,
-    is_current_branch: bool,
-    marker: Option<String>,
-    matched: Option<(usize, usize)>,
-    nomad_style: &NomadStyle,
-    number: Option<i32>,
-    upstream: Option<String>,
-) {
-    let branch_label = match matched {
+fn display_flat_branch(found_branch: &FoundBranch, nomad_style: &NomadStyle, number: Option<i32>) {
+    let branch_label = match found_branch.matched {
         Some(matched) => highlight_matched(
             false,
             nomad_style,
-            branch_name.to_string(),
+            found_branch.full_branch.to_string(),
             (matched.0, matched.1),
         ),
-        None => branch_name.to_string(),
+        None => found_branch.full_branch.to_string(),
     };
-    let formatted_branch = if is_current_branch {
+    let formatted_branch = if found_branch.is_current_branch {
         Colour::Green.bold().paint(branch_label).to_string()
     } else {
         branch_label
@@ -268,22 +278,214 @@ fn display_flat_branch(
         ),
         None => "".to_string(),
     };
-    let marker_label = match marker {
+    let marker_label = match &found_branch.marker {
         Some(marker) => format!("{} ", Colour::Green.bold().paint(marker)),
         None => "".to_string(),
     };
-    let head_label = if branch.is_head() {
+    let head_label = if found_branch.is_head {
         format!(" [{}]", Colour::Red.bold().paint("HEAD"))
     } else {
         "".to_string()
     };
-    let upstream_label = match upstream {
-        Some(upstream_branch) => upstream_branch,
+    let upstream_label = match &found_branch.upstream {
+        Some(upstream_branch) => upstream_branch.to_string(),
         None => "".to_string(),
     };
 
     println!(
-        "{}{}{}{}{}",
-        number_label, marker_label, formatted_branch, head_label, upstream_label
+        "{}{}{}{}{}{}",
+        number_label,
+        marker_label,
+        formatted_branch,
+        head_label,
+        upstream_label,
+        format_branch_details(found_branch.ahead_behind, found_branch.last_commit_time)
     );
 }
+
+/// Get the commit that branches are compared against when filtering by `--merged`
+/// or `--no-merged`, which is `HEAD` if a revision was not provided.
+fn get_merge_base(revision: Option<&str>, repo: &Repository) -> Result<Oid, NomadError> {
+    Ok(repo
+        .revparse_single(revision.unwrap_or("HEAD"))?
+        .peel_to_commit()?
+        .id())
+}
+
+/// Check whether the commit is merged into the base, meaning it is the base itself
+/// or one of its ancestors.
+fn is_merged(commit: Oid, base: Oid, repo: &Repository) -> Result<bool, NomadError> {
+    Ok(commit == base || repo.graph_descendant_of(base, commit)?)
+}
+
+/// Get the local branch matching the label. The label may be a number from the last
+/// branch tree or list, or the name of a branch.
+///
+/// Remote-tracking branches are numbered as `remotes/<remote>/<branch>` when `--all`
+/// is passed, but cannot be switched to, deleted, or renamed.
+fn get_labeled_branch<'a>(label: &str, repo: &'a Repository) -> Result<Branch<'a>, NomadError> {
+    let branch_name = get_deserialized_json()
+        .ok()
+        .and_then(|contents| contents.branches.get(label).cloned())
+        .unwrap_or_else(|| label.to_string());
+
+    repo.find_branch(&branch_name, BranchType::Local)
+        .map_err(|error| {
+            match branch_name
+                .strip_prefix("remotes/")
+                .and_then(|remote_name| repo.find_branch(remote_name, BranchType::Remote).ok())
+            {
+                Some(_) => NomadError::Error(anyhow!(
+                    "{branch_name} is a remote-tracking branch! Only local branches may be used here."
+                )),
+                None => NomadError::GitError {
+                    context: format!("Could not find a branch matching {label}"),
+                    source: error,
+                },
+            }
+        })
+}
+
+/// Create a new branch pointing to the revision, or to `HEAD` if a revision was not
+/// provided. The current branch is not changed.
+pub fn create_branch(
+    branch_name: &str,
+    revision: Option<&str>,
+    repo: &Repository,
+) -> Result<(), NomadError> {
+    let commit = repo
+        .revparse_single(revision.unwrap_or("HEAD"))?
+        .peel_to_commit()?;
+    repo.branch(branch_name, &commit, false)?;
+
+    println!(
+        "\nCreated branch {} at {}\n",
+        Colour::Green.bold().paint(branch_name),
+        Colour::Fixed(193)
+            .bold()
+            .paint(&commit.id().to_string()[..7])
+    );
+
+    Ok(())
+}
+
+/// Delete the labeled branches. Branches that are not merged into `HEAD` are not
+/// deleted unless `force` is set, and the current branch is never deleted.
+///
+/// Every label is checked before any branch is deleted, so nothing is deleted if
+/// any of the labels cannot be.
+pub fn delete_branches(
+    labels: &[String],
+    force: bool,
+    repo: &Repository,
+) -> Result<(), NomadError> {
+    let head = repo.head().ok().and_then(|head| head.target());
+
+    let mut branches: Vec<(Branch, String, Option<Oid>)> = Vec::new();
+    for label in labels {
+        let branch = get_labeled_branch(label, repo)?;
+        let branch_name = branch.name()?.unwrap_or("?").to_string();
+        let target = branch.get().target();
+
+        if branch.is_head() {
+            return Err(NomadError::Error(anyhow!(
+                "Cannot delete {branch_name} because it is the current branch!"
+            )));
+        }
+
+        let is_merged = match (head, target) {
+            (Some(head), Some(target)) => is_merged(target, head, repo)?,
+            _ => false,
+        };
+        if !is_merged && !force {
+            return Err(NomadError::Error(anyhow!(
+                "{branch_name} is not merged into HEAD! Use `--force` to delete it anyway."
+            )));
+        }
+
+        // A branch may be labeled by both its number and its name.
+        if !branches.iter().any(|(_, name, _)| name == &branch_name) {
+            branches.push((branch, branch_name, target));
+        }
+    }
+
+    for (mut branch, branch_name, target) in branches {
+        branch.delete()?;
+
+        println!(
+            "Deleted branch {} (was {})",
+            Colour::Red.bold().paint(&branch_name),
+            Colour::Fixed(193).bold().paint(target.map_or_else(
+                || "?".to_string(),
+                |target| target.to_string()[..7].to_string()
+            ))
+        );
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Rename the labeled branch.
+pub fn rename_branch(label: &str, new_name: &str, repo: &Repository) -> Result<(), NomadError> {
+    let mut branch = get_labeled_branch(label, repo)?;
+    let old_name = branch.name()?.unwrap_or("?").to_string();
+
+    branch.rename(new_name, false)?;
+
+    println!(
+        "\nRenamed branch {} to {}\n",
+        Colour::Fixed(172).bold().paint(old_name),
+        Colour::Green.bold().paint(new_name)
+    );
+
+    Ok(())
+}
+
+/// Switch to the labeled branch and update the working tree to match it.
+///
+/// Switching is refused if tracked files contain changes, since they would be
+/// overwritten. Changes are discarded instead if `force` is set. Untracked files
+/// are left alone.
+pub fn switch_branch(label: &str, force: bool, repo: &Repository) -> Result<(), NomadError> {
+    let branch = get_labeled_branch(label, repo)?;
+    let branch_name = branch.name()?.unwrap_or("?").to_string();
+
+    if branch.is_head() {
+        println!("\nAlready on {}\n", Colour::Green.bold().paint(branch_name));
+
+        return Ok(());
+    }
+
+    if !force {
+        let mut status_options = StatusOptions::new();
+        status_options.include_untracked(false);
+
+        if !repo.statuses(Some(&mut status_options))?.is_empty() {
+            return Err(NomadError::Error(anyhow!(
+                "Your working tree contains uncommitted changes! Commit them or use `--force` to discard them."
+            )));
+        }
+    }
+
+    let reference = branch.get();
+    let mut checkout_options = CheckoutBuilder::new();
+    if force {
+        checkout_options.force();
+    } else {
+        checkout_options.safe();
+    }
+
+    repo.checkout_tree(
+        &reference.peel(ObjectType::Commit)?,
+        Some(&mut checkout_options),
+    )?;
+    repo.set_head(reference.name().unwrap_or("?"))?;
+
+    println!(
+        "\nSwitched to branch {}\n",
+        Colour::Green.bold().paint(branch_name)
+    );
+
+    Ok(())
+}
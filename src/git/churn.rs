@@ -0,0 +1,355 @@
+//! Display how often each item changed in tree form from the commit history.
+
+use super::revision::{get_relative_path, get_revision_commit};
+use crate::{
+    cli::{
+        git,
+        global::{GlobalArgs, GraphArgs, LabelArgs, MetaArgs, ModifierArgs, RegexArgs, StyleArgs},
+    },
+    errors::NomadError,
+    models::JsonItem,
+    style::models::NomadStyle,
+    traverse::{
+        models::{FoundItem, ItemChurn, LineChanges, TransformedItem},
+        modes::NomadMode,
+        traits::{ToJson, ToTree, TransformFound},
+        utils::{get_sort_options, set_line_changes, sort_items},
+    },
+};
+
+use ansi_term::Colour;
+use anyhow::Result;
+use git2::{DiffOptions, Patch, Repository, Sort};
+use ptree::{item::StringItem, PrintConfig};
+use regex::Regex;
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// Build a tree of the files within the target directory that were touched by the
+/// commits reachable from `HEAD`. Each item displays the number of commits that
+/// touched it and the number of lines they added and deleted.
+pub fn display_churn_tree(
+    args: &git::ChurnOptions,
+    nomad_style: &NomadStyle,
+    repo: &Repository,
+    target_directory: &str,
+) -> Result<Option<(StringItem, PrintConfig, Option<JsonItem>)>, NomadError> {
+    let global_args = GlobalArgs {
+        export: args.export.clone(),
+        export_format: args.export_format,
+        graph: GraphArgs {
+            graph_depth: args.graph.graph_depth,
+            graph_max_nodes: args.graph.graph_max_nodes,
+        },
+        json: args.json,
+        labels: LabelArgs {
+            all_labels: args.labels.all_labels,
+            label_directories: args.labels.label_directories,
+            numbers: args.labels.numbers,
+        },
+        meta: MetaArgs::default(),
+        modifiers: ModifierArgs {
+            reverse: args.reverse,
+            sort: args.sort,
+            ..Default::default()
+        },
+        regex: RegexArgs {
+            grep: None,
+            pattern: args.pattern.clone(),
+        },
+        style: StyleArgs {
+            no_colors: args.style.no_colors,
+            no_git: args.style.no_git,
+            no_icons: args.style.no_icons,
+            plain: args.style.plain,
+        },
+        statistics: args.statistics,
+        stream: false,
+    };
+
+    let transformed_items = sort_items(
+        get_churn_items(args, repo, target_directory)?,
+        &get_sort_options(&global_args, nomad_style),
+        target_directory,
+    );
+
+    if global_args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(
+                &transformed_items.to_json(&global_args, target_directory)?
+            )?
+        );
+
+        Ok(None)
+    } else if transformed_items.is_empty() {
+        println!(
+            "\n{}\n",
+            Colour::Green
+                .bold()
+                .paint("No files were changed.".to_string())
+        );
+
+        Ok(None)
+    } else {
+        let json_item = match (&global_args.export, global_args.export_format) {
+            (Some(_), export_format) if export_format.is_structured() => {
+                Some(transformed_items.to_json(&global_args, target_directory)?)
+            }
+            _ => None,
+        };
+
+        let (tree, config, _) = transformed_items.to_tree(
+            &global_args,
+            NomadMode::Normal,
+            nomad_style,
+            target_directory,
+        )?;
+
+        Ok(Some((tree, config, json_item)))
+    }
+}
+
+/// Walk the history from `HEAD` once and transform the files within the target
+/// directory that were touched for tree building.
+///
+/// Each commit is compared against its first parent, and merges are skipped so that
+/// changes are only counted once. A directory's commits are the commits that touched
+/// any file within it, so they are not the sum of its files' commits. Files that no
+/// longer exist at `HEAD` and hidden files are left out.
+fn get_churn_items(
+    args: &git::ChurnOptions,
+    repo: &Repository,
+    target_directory: &str,
+) -> Result<Vec<TransformedItem>, NomadError> {
+    let head = get_revision_commit(repo, "HEAD")?;
+    let head_tree = head.tree()?;
+    let prefix = get_relative_path(repo, target_directory)?;
+
+    let regex_expression = match args.pattern {
+        Some(ref pattern) => Some(Regex::new(pattern).map_err(NomadError::RegexError)?),
+        None => None,
+    };
+    let since = args
+        .since
+        .and_then(|since| since.0.duration_since(UNIX_EPOCH).ok())
+        .and_then(|duration| i64::try_from(duration.as_secs()).ok());
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(head.id())?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+
+    let mut item_commits: HashMap<String, usize> = HashMap::new();
+    let mut file_changes: HashMap<String, LineChanges> = HashMap::new();
+
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_count() > 1 || since.is_some_and(|since| commit.time().seconds() < since) {
+            continue;
+        }
+
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+
+        let mut diff_options = DiffOptions::new();
+        diff_options.ignore_submodules(true);
+
+        let diff = repo.diff_tree_to_tree(
+            parent_tree.as_ref(),
+            Some(&commit.tree()?),
+            Some(&mut diff_options),
+        )?;
+
+        let mut touched_files: Vec<PathBuf> = Vec::new();
+        for (index, delta) in diff.deltas().enumerate() {
+            let path = match delta.new_file().path().or_else(|| delta.old_file().path()) {
+                Some(path) => path,
+                None => continue,
+            };
+            let relative_path = match path.strip_prefix(&prefix) {
+                Ok(relative_path) => relative_path.to_str().unwrap_or("?"),
+                Err(_) => continue,
+            };
+
+            if head_tree.get_path(path).is_err()
+                || relative_path.split('/').any(|name| name.starts_with('.'))
+                || regex_expression
+                    .as_ref()
+                    .is_some_and(|regex| !regex.is_match(relative_path))
+            {
+                continue;
+            }
+
+            let (_, added, deleted) = match Patch::from_diff(&diff, index)? {
+                Some(patch) => patch.line_stats()?,
+                None => (0, 0, 0),
+            };
+
+            let absolute_path = Path::new(target_directory).join(relative_path);
+            let total = file_changes
+                .entry(absolute_path.to_str().unwrap_or("?").to_string())
+                .or_default();
+            total.added += added;
+            total.deleted += deleted;
+
+            touched_files.push(absolute_path);
+        }
+
+        for path in get_touched_items(&touched_files, target_directory) {
+            *item_commits.entry(path).or_insert(0) += 1;
+        }
+    }
+
+    if file_changes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut found_items = file_changes
+        .keys()
+        .map(|path| FoundItem {
+            line_matches: Vec::new(),
+            marker: None,
+            matched: regex_expression.as_ref().and_then(|regex| {
+                regex
+                    .find(
+                        Path::new(path)
+                            .strip_prefix(target_directory)
+                            .ok()
+                            .and_then(|relative_path| relative_path.to_str())
+                            .unwrap_or("?"),
+                    )
+                    .map(|matched| (matched.start(), matched.end()))
+            }),
+            path: path.to_string(),
+            status: None,
+        })
+        .collect::<Vec<FoundItem>>();
+    found_items.sort_by(|first, second| Path::new(&first.path).cmp(Path::new(&second.path)));
+
+    let mut transformed_items = found_items.transform(target_directory)?;
+    set_line_changes(&mut transformed_items, &file_changes, target_directory);
+
+    // Directories always have at least as many commits as the files within them, so
+    // files and directories are heated separately. The items touched by the fewest
+    // commits are the coolest.
+    let get_item_range = |is_dir: bool| {
+        get_commit_range(
+            transformed_items
+                .iter()
+                .filter(|item| item.is_dir == is_dir)
+                .filter_map(|item| item_commits.get(&item.path).copied()),
+        )
+    };
+    let (file_range, directory_range) = (get_item_range(false), get_item_range(true));
+
+    for item in transformed_items.iter_mut() {
+        let commit_range = if item.is_dir {
+            directory_range
+        } else {
+            file_range
+        };
+
+        item.churn = item_commits.get(&item.path).map(|commits| ItemChurn {
+            commits: *commits,
+            heat: get_heat(*commits, commit_range),
+        });
+    }
+
+    Ok(transformed_items)
+}
+
+/// Get the files and every directory containing them below the target directory.
+/// Each item is only included once, so a directory only counts one commit even if
+/// the commit touched several files within it.
+fn get_touched_items(touched_files: &[PathBuf], target_directory: &str) -> HashSet<String> {
+    let mut touched_items = HashSet::new();
+
+    for touched_file in touched_files {
+        for ancestor in touched_file.ancestors() {
+            if ancestor == Path::new(target_directory) {
+                break;
+            }
+
+            touched_items.insert(ancestor.to_str().unwrap_or("?").to_string());
+        }
+    }
+
+    touched_items
+}
+
+/// Get the fewest and the most commits that touched any of the items. Both are 1 if
+/// there are no items.
+fn get_commit_range(commits: impl Iterator<Item = usize> + Clone) -> (usize, usize) {
+    (
+        commits.clone().min().unwrap_or(1),
+        commits.max().unwrap_or(1),
+    )
+}
+
+/// Get the heat of an item from 0.0 for the fewest commits to 1.0 for the most
+/// commits. Every item is the hottest if all items were touched by the same number
+/// of commits.
+fn get_heat(commits: usize, (min_commits, max_commits): (usize, usize)) -> f64 {
+    if max_commits > min_commits {
+        (commits - min_commits) as f64 / (max_commits - min_commits) as f64
+    } else {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod test_churn {
+    use super::*;
+
+    #[test]
+    fn test_get_touched_items() {
+        let touched_files = vec![
+            PathBuf::from("/root/src/git/churn.rs"),
+            PathBuf::from("/root/src/git/diff.rs"),
+            PathBuf::from("/root/src/main.rs"),
+        ];
+
+        let mut touched_items = get_touched_items(&touched_files, "/root")
+            .into_iter()
+            .collect::<Vec<String>>();
+        touched_items.sort();
+
+        // Directories are counted once and the target directory is left out.
+        assert_eq!(
+            touched_items,
+            vec![
+                "/root/src",
+                "/root/src/git",
+                "/root/src/git/churn.rs",
+                "/root/src/git/diff.rs",
+                "/root/src/main.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_commit_range() {
+        assert_eq!(get_commit_range([3, 1, 7, 4].into_iter()), (1, 7));
+        assert_eq!(get_commit_range([5].into_iter()), (5, 5));
+        assert_eq!(get_commit_range(std::iter::empty()), (1, 1));
+    }
+
+    #[test]
+    fn test_get_heat() {
+        assert_eq!(get_heat(1, (1, 5)), 0.0);
+        assert_eq!(get_heat(3, (1, 5)), 0.5);
+        assert_eq!(get_heat(5, (1, 5)), 1.0);
+    }
+
+    #[test]
+    fn test_get_heat_equal_commits() {
+        assert_eq!(get_heat(4, (4, 4)), 1.0);
+        assert_eq!(get_heat(1, get_commit_range(std::iter::empty())), 1.0);
+    }
+}
@@ -1,70 +1,446 @@
 //! Commit staged changes in the Git repository.
 
 use ansi_term::Colour;
-use git2::{ObjectType, Repository};
+use anyhow::anyhow;
+use git2::{message_prettify, ErrorCode, Repository, Signature, Status};
+use ptree::{write_tree_with, TreeBuilder};
+
+use std::{
+    ffi::OsStr,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 use crate::{
+    cli::{git::CommitOptions, global::StyleArgs},
     errors::NomadError,
     git::{
         diff::get_diff_stats,
-        utils::{get_last_commit, get_repo_branch},
+        markers::{get_status_marker, get_statuses},
+        utils::{get_last_commit, get_repo_branch, get_repo_root},
     },
+    style::models::NomadStyle,
+    traverse::utils::build_tree_style,
+    utils::open::edit_file,
 };
 
 /// Commit the staged changes with an accompanying message if applicable.
-pub fn commit_changes(message: &Option<String>, repo: &Repository) -> Result<(), NomadError> {
-    match repo.signature() {
-        Ok(signature) => {
-            let checked_message = if let Some(message) = message {
-                message.to_string()
-            } else {
-                "Updating".to_string()
-            };
-
-            let mut index = repo.index()?;
-            let staged_tree = repo.find_tree(index.write_tree()?)?;
-
-            let previous_head = repo.head()?.peel(ObjectType::Tree)?.id();
-
-            let parent_commit = get_last_commit(repo)?;
-            let commit_oid = repo
-                .commit(
-                    Some("HEAD"),
-                    &signature,
-                    &signature,
-                    &checked_message,
-                    &staged_tree,
-                    &[&parent_commit],
-                )?
-                .to_string();
-
-            let branch_name = get_repo_branch(repo).unwrap_or_else(|| "?".to_string());
-            let branch = Colour::Green.bold().paint(branch_name).to_string();
-
-            let sliced_oid = &commit_oid[..7];
-
-            println!("\n[{branch} {sliced_oid}] {checked_message}\n");
-
-            let old_tree = repo.find_tree(previous_head)?;
-            if let (Some(files_changed), Some(insertions), Some(deletions)) =
-                get_diff_stats(&mut index, &old_tree, repo)
-            {
-                println!(
-                    "| {colored_changed} {changed_label} changed | {colored_insertions} {insertions_label} | {colored_deletions} {deletions_label} |\n",
-                    colored_changed = Colour::Fixed(172).bold().paint(format!("{files_changed}")),
-                    changed_label = if files_changed == 1 { "file" } else { "files" },
-                    colored_insertions = Colour::Green.bold().paint(format!("+{insertions}")),
-                    insertions_label = if insertions == 1 { "insertion" } else { "insertions" },
-                    colored_deletions = Colour::Red.bold().paint(format!("-{deletions}")),
-                    deletions_label = if deletions == 1 { "deletion" } else { "deletions" },
-                );
+///
+/// The repository's `pre-commit` hook is run before the staged changes are read, and
+/// its `commit-msg` hook is run on the final message. The commit is aborted if either
+/// hook fails. If no message was provided, the message is written in an editor.
+pub fn commit_changes(
+    args: &CommitOptions,
+    nomad_style: &NomadStyle,
+    repo: &Repository,
+    target_directory: &str,
+) -> Result<(), NomadError> {
+    let signature = repo.signature().map_err(|error| NomadError::GitError {
+        context: "Unable to commit changes without a Git signature".into(),
+        source: error,
+    })?;
+
+    // The branch does not contain any commits before the first commit is made.
+    let head_commit = match repo.head() {
+        Ok(_) => Some(get_last_commit(repo)?),
+        Err(error) if error.code() == ErrorCode::UnbornBranch => None,
+        Err(error) => return Err(NomadError::PlainGitError(error)),
+    };
+    let amended_commit = match (args.amend, &head_commit) {
+        (true, Some(head_commit)) => Some(head_commit),
+        (true, None) => {
+            return Err(NomadError::Error(anyhow!(
+                "There is no commit to amend on this branch!"
+            )))
+        }
+        (false, _) => None,
+    };
+
+    run_hook("pre-commit", &[], repo)?;
+
+    // The `pre-commit` hook may have staged changes of its own.
+    let mut index = repo.index()?;
+    index.read(false)?;
+    let staged_tree = repo.find_tree(index.write_tree()?)?;
+
+    // Staged changes are compared against the parent of the new commit, which is the
+    // parent of the amended commit when amending.
+    let parent_tree = match amended_commit {
+        Some(amended_commit) => match amended_commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        },
+        None => match head_commit {
+            Some(ref head_commit) => Some(head_commit.tree()?),
+            None => None,
+        },
+    };
+
+    let no_changes = match parent_tree {
+        Some(ref parent_tree) => parent_tree.id() == staged_tree.id(),
+        None => staged_tree.is_empty(),
+    };
+    if no_changes && !args.allow_empty && !args.amend {
+        return Err(NomadError::Error(anyhow!(
+            "No changes were staged! Use `--allow-empty` to commit anyway."
+        )));
+    }
+
+    let message_path = repo.path().join("COMMIT_EDITMSG");
+    let mut message = match &args.message {
+        Some(message) => message_prettify(message.as_str(), None)?,
+        None => {
+            fs::write(
+                &message_path,
+                get_message_template(
+                    amended_commit.and_then(|commit| commit.message()),
+                    nomad_style,
+                    repo,
+                    target_directory,
+                )?,
+            )?;
+            edit_file(&message_path)?;
+
+            message_prettify(fs::read_to_string(&message_path)?, Some(b'#'))?
+        }
+    };
+    if message.is_empty() {
+        return Err(NomadError::Error(anyhow!(
+            "Aborting commit due to an empty commit message!"
+        )));
+    }
+
+    if args.signoff {
+        message = add_signoff(&message, &signature);
+    }
+
+    // The `commit-msg` hook may edit the message in place.
+    fs::write(&message_path, &message)?;
+    run_hook("commit-msg", &[&message_path], repo)?;
+    let message = message_prettify(fs::read_to_string(&message_path)?, None)?;
+    if message.is_empty() {
+        return Err(NomadError::Error(anyhow!(
+            "Aborting commit due to an empty commit message!"
+        )));
+    }
+
+    let commit_oid = match amended_commit {
+        Some(amended_commit) => amended_commit.amend(
+            Some("HEAD"),
+            None,
+            Some(&signature),
+            None,
+            Some(&message),
+            Some(&staged_tree),
+        )?,
+        None => repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &message,
+            &staged_tree,
+            &head_commit.iter().collect::<Vec<_>>(),
+        )?,
+    }
+    .to_string();
+
+    let branch_name = get_repo_branch(repo).unwrap_or_else(|| "?".to_string());
+    let branch = Colour::Green.bold().paint(branch_name).to_string();
+    let root_commit = match head_commit {
+        Some(_) => "",
+        None => " (root-commit)",
+    };
+
+    let sliced_oid = &commit_oid[..7];
+    let summary = message.lines().next().unwrap_or("");
+
+    println!("\n[{branch}{root_commit} {sliced_oid}] {summary}\n");
+
+    if let (Some(files_changed), Some(insertions), Some(deletions)) =
+        get_diff_stats(&mut index, parent_tree.as_ref(), repo)
+    {
+        println!(
+            "| {colored_changed} {changed_label} changed | {colored_insertions} {insertions_label} | {colored_deletions} {deletions_label} |\n",
+            colored_changed = Colour::Fixed(172).bold().paint(format!("{files_changed}")),
+            changed_label = if files_changed == 1 { "file" } else { "files" },
+            colored_insertions = Colour::Green.bold().paint(format!("+{insertions}")),
+            insertions_label = if insertions == 1 { "insertion" } else { "insertions" },
+            colored_deletions = Colour::Red.bold().paint(format!("-{deletions}")),
+            deletions_label = if deletions == 1 { "deletion" } else { "deletions" },
+        );
+    }
+
+    Ok(())
+}
+
+/// Get the template that is opened in the editor when a message was not provided.
+/// The template starts with the previous message when amending and lists the staged
+/// changes in tree form in the comments below it.
+fn get_message_template(
+    previous_message: Option<&str>,
+    nomad_style: &NomadStyle,
+    repo: &Repository,
+    target_directory: &str,
+) -> Result<String, NomadError> {
+    let mut template = format!(
+        "{}\n# Please enter the commit message for your changes. Lines starting\n# with '#' will be ignored, and an empty message aborts the commit.\n#\n",
+        previous_message.unwrap_or("")
+    );
+
+    // `HEAD` points to the branch by name, even if the branch does not contain any
+    // commits yet.
+    if let Some(branch_name) = repo.find_reference("HEAD").ok().and_then(|head| {
+        head.symbolic_target()
+            .map(|target| target.trim_start_matches("refs/heads/").to_string())
+    }) {
+        template.push_str(&format!("# On branch {branch_name}\n#\n"));
+    }
+
+    match get_staged_tree(nomad_style, repo, target_directory)? {
+        Some(staged_tree) => {
+            template.push_str("# Changes to be committed:\n#\n");
+            for line in staged_tree.lines() {
+                template.push_str(&format!("# {line}\n"));
             }
+        }
+        None => template.push_str("# No changes\n"),
+    }
+
+    Ok(template)
+}
+
+/// Write the staged items of the repository in tree form without any colors or
+/// icons. Each item is labeled with its staged status marker.
+fn get_staged_tree(
+    nomad_style: &NomadStyle,
+    repo: &Repository,
+    target_directory: &str,
+) -> Result<Option<String>, NomadError> {
+    let repo_root = get_repo_root(repo)?;
+    let staged = Status::INDEX_NEW
+        | Status::INDEX_MODIFIED
+        | Status::INDEX_DELETED
+        | Status::INDEX_RENAMED
+        | Status::INDEX_TYPECHANGE;
+
+    let mut staged_items = get_statuses(repo, false, target_directory)?
+        .into_iter()
+        .filter(|(_, status)| status.intersects(staged))
+        .map(|(path, status)| {
+            (
+                Path::new(&path)
+                    .strip_prefix(&repo_root)
+                    .map_or_else(|_| PathBuf::from(&path), |path| path.to_path_buf()),
+                // Unstaged changes in the same file are not committed.
+                status & staged,
+            )
+        })
+        .collect::<Vec<(PathBuf, Status)>>();
+    if staged_items.is_empty() {
+        return Ok(None);
+    }
+    staged_items.sort_by(|(first, _), (second, _)| first.cmp(second));
+
+    let style_args = StyleArgs {
+        no_colors: true,
+        no_git: false,
+        no_icons: true,
+        plain: false,
+    };
+
+    let mut tree = TreeBuilder::new(
+        repo_root
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("?")
+            .to_string(),
+    );
+    let mut open_directories: Vec<&OsStr> = Vec::new();
+
+    for (path, status) in staged_items.iter() {
+        let directories = path
+            .parent()
+            .map_or_else(Vec::new, |parent| parent.iter().collect::<Vec<&OsStr>>());
+        let shared_depth = open_directories
+            .iter()
+            .zip(directories.iter())
+            .take_while(|(open, directory)| open == directory)
+            .count();
+
+        for _ in shared_depth..open_directories.len() {
+            tree.end_child();
+        }
+        open_directories.truncate(shared_depth);
+
+        for directory in directories.into_iter().skip(shared_depth) {
+            tree.begin_child(directory.to_str().unwrap_or("?").to_string());
+            open_directories.push(directory);
+        }
+
+        tree.add_empty_child(format!(
+            "{} {}",
+            get_status_marker(&style_args, nomad_style, *status),
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("?")
+        ));
+    }
+
+    let mut buffer = Vec::new();
+    write_tree_with(&tree.build(), &mut buffer, &build_tree_style(nomad_style))?;
+
+    Ok(Some(String::from_utf8_lossy(&buffer).to_string()))
+}
+
+/// Add a `Signed-off-by` trailer for the committer to the end of the message unless
+/// the trailers at the end of the message already contain it. The trailer joins the
+/// trailers at the end of the message if there are any.
+fn add_signoff(message: &str, signature: &Signature) -> String {
+    let signoff = format!(
+        "Signed-off-by: {} <{}>",
+        signature.name().unwrap_or("Unknown author"),
+        signature.email().unwrap_or("Unknown email")
+    );
+
+    // The trailers are the last paragraph of the message if every line within it is
+    // a trailer. The subject is never treated as a trailer.
+    let message = message.trim_end();
+    let trailers = match message.rsplit_once("\n\n") {
+        Some((_, last_paragraph)) if last_paragraph.lines().all(is_trailer) => Some(last_paragraph),
+        _ => None,
+    };
+
+    match trailers {
+        Some(trailers) if trailers.lines().any(|line| line == signoff) => format!("{message}\n"),
+        Some(_) => format!("{message}\n{signoff}\n"),
+        None => format!("{message}\n\n{signoff}\n"),
+    }
+}
+
+/// Check whether the line is a trailer, ie. `Reviewed-by: Alice <alice@example.com>`.
+fn is_trailer(line: &str) -> bool {
+    match line.split_once(": ") {
+        Some((token, _)) => {
+            !token.is_empty()
+                && token
+                    .chars()
+                    .all(|character| character.is_ascii_alphanumeric() || character == '-')
+        }
+        None => false,
+    }
+}
+
+/// Run a hook from the repository's hooks directory, or the directory set in
+/// `core.hooksPath`, if the hook exists.
+///
+/// Hooks that are not executable are skipped. Returns an error if the hook fails so
+/// that the commit is aborted.
+fn run_hook(hook: &str, hook_args: &[&Path], repo: &Repository) -> Result<(), NomadError> {
+    let working_directory = repo.workdir().unwrap_or_else(|| repo.path());
+    let hooks_directory = match repo.config()?.get_path("core.hooksPath") {
+        Ok(hooks_path) => working_directory.join(hooks_path),
+        Err(_) => repo.path().join("hooks"),
+    };
+
+    let hook_path = hooks_directory.join(hook);
+    if !hook_path.is_file() {
+        return Ok(());
+    }
+
+    match Command::new(&hook_path)
+        .args(hook_args)
+        .current_dir(working_directory)
+        .status()
+    {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(NomadError::Error(anyhow!(
+            "The {hook} hook failed ({status}). Aborting commit."
+        ))),
+        Err(error) if error.kind() == ErrorKind::PermissionDenied => {
+            println!(
+                "{}",
+                Colour::Fixed(172).bold().paint(format!(
+                    "The {hook} hook was ignored because it is not executable."
+                ))
+            );
 
             Ok(())
         }
-        Err(error) => Err(NomadError::GitError {
-            context: "Unable to commit changes without a Git signature".into(),
-            source: error,
-        }),
+        Err(error) => Err(NomadError::IOError(error)),
+    }
+}
+
+#[cfg(test)]
+mod test_commit {
+    use super::*;
+
+    const SIGNOFF: &str = "Signed-off-by: Alice <alice@example.com>";
+
+    /// Sign off the message as Alice.
+    fn signoff(message: &str) -> String {
+        add_signoff(
+            message,
+            &Signature::now("Alice", "alice@example.com").unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_add_signoff_subject() {
+        assert_eq!(
+            signoff("Fix the parser\n"),
+            format!("Fix the parser\n\n{SIGNOFF}\n")
+        );
+    }
+
+    #[test]
+    fn test_add_signoff_body() {
+        assert_eq!(
+            signoff("Fix the parser\n\nThe parser dropped the last line.\n\n"),
+            format!("Fix the parser\n\nThe parser dropped the last line.\n\n{SIGNOFF}\n")
+        );
+    }
+
+    #[test]
+    fn test_add_signoff_joins_trailers() {
+        assert_eq!(
+            signoff("Fix the parser\n\nReviewed-by: Bob <bob@example.com>\n"),
+            format!("Fix the parser\n\nReviewed-by: Bob <bob@example.com>\n{SIGNOFF}\n")
+        );
+        assert_eq!(
+            signoff("Fix the parser\n\nSigned-off-by: Bob <bob@example.com>\n"),
+            format!("Fix the parser\n\nSigned-off-by: Bob <bob@example.com>\n{SIGNOFF}\n")
+        );
+    }
+
+    #[test]
+    fn test_add_signoff_existing_signoff() {
+        let message = format!("Fix the parser\n\n{SIGNOFF}\n");
+        assert_eq!(signoff(&message), message);
+
+        let message = format!("Fix the parser\n\n{SIGNOFF}\nReviewed-by: Bob <bob@example.com>\n");
+        assert_eq!(signoff(&message), message);
+    }
+
+    #[test]
+    fn test_add_signoff_not_trailers() {
+        // The subject is not a trailer even if it looks like one.
+        assert_eq!(
+            signoff("parser: Fix the last line\n"),
+            format!("parser: Fix the last line\n\n{SIGNOFF}\n")
+        );
+
+        // Every line in the last paragraph has to be a trailer.
+        assert_eq!(
+            signoff("Fix the parser\n\nNote: the parser\ndropped the last line.\n"),
+            format!("Fix the parser\n\nNote: the parser\ndropped the last line.\n\n{SIGNOFF}\n")
+        );
+
+        // A signoff in the body is not in the trailers.
+        assert_eq!(
+            signoff(&format!("Fix the parser\n\n{SIGNOFF}\n\nMore details.\n")),
+            format!("Fix the parser\n\n{SIGNOFF}\n\nMore details.\n\n{SIGNOFF}\n")
+        );
     }
 }
@@ -1,11 +1,13 @@
 //! Exposing functionality for the Git diff command.
 
-use std::{ffi::OsStr, path::Path, str::from_utf8};
+use std::{collections::HashMap, ffi::OsStr, path::Path, str::from_utf8};
 
 use ansi_term::Colour;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use bat::{Input, PagingMode, PrettyPrinter, WrappingMode};
-use git2::{Delta, Diff, DiffDelta, DiffFormat, Error, Index, ObjectType, Repository, Tree};
+use git2::{
+    Delta, Diff, DiffDelta, DiffFindOptions, DiffFormat, Error, Index, Oid, Patch, Repository, Tree,
+};
 use lazy_static::lazy_static;
 use syntect::{
     easy::HighlightLines,
@@ -13,7 +15,28 @@ use syntect::{
     util::as_24_bit_terminal_escaped,
 };
 
-use crate::{errors::NomadError, SYNTAX_SET, THEME_SET};
+use super::{revision::get_revision_commit, utils::get_repo_root};
+use crate::{
+    cli::{
+        git,
+        global::{GlobalArgs, GraphArgs, LabelArgs, MetaArgs, ModifierArgs, RegexArgs, StyleArgs},
+    },
+    errors::NomadError,
+    models::DiffRange,
+    style::models::NomadStyle,
+    traverse::{
+        models::{FoundItem, LineChanges},
+        modes::NomadMode,
+        traits::{ToTree, TransformFound},
+        utils::{get_sort_options, set_line_changes, sort_items},
+    },
+    utils::{
+        cache::{get_json_file, write_to_json},
+        export::ExportFormat,
+        open::get_deserialized_json,
+    },
+    SYNTAX_SET, THEME_SET,
+};
 
 lazy_static! {
     /// A dark green color to indicate added lines.
@@ -39,16 +62,296 @@ lazy_static! {
     };
 }
 
-/// Get the diff between the old Git tree and the working directory using the Git index.
-pub fn get_repo_diffs(repo: &Repository) -> Result<Diff<'_>, Error> {
-    let previous_head = repo.head()?.peel(ObjectType::Tree)?.id();
-    let old_tree = repo.find_tree(previous_head)?;
+/// Get the diff between the two sides of the diff range. Renamed files are detected
+/// as well.
+pub fn get_repo_diffs<'a>(diff_range: &DiffRange, repo: &'a Repository) -> Result<Diff<'a>, Error> {
+    let mut diff = match diff_range {
+        DiffRange::Commits { old, new } => repo.diff_tree_to_tree(
+            Some(&repo.revparse_single(old)?.peel_to_tree()?),
+            Some(&repo.revparse_single(new)?.peel_to_tree()?),
+            None,
+        )?,
+        DiffRange::Staged { old } => repo.diff_tree_to_index(
+            Some(&repo.revparse_single(old)?.peel_to_tree()?),
+            None,
+            None,
+        )?,
+        DiffRange::WorkingDirectory { old } => repo.diff_tree_to_workdir_with_index(
+            Some(&repo.revparse_single(old)?.peel_to_tree()?),
+            None,
+        )?,
+    };
 
-    let diff = repo.diff_tree_to_workdir_with_index(Some(&old_tree), None)?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
 
     Ok(diff)
 }
 
+/// Get the diff range from the revisions passed into `--tree` and `--staged`.
+///
+/// A range such as `A..B` compares two commits, and `A...B` compares `B` with the
+/// commit at which it branched off of `A`. Either side may be left out to use `HEAD`.
+/// A single revision, or no revision at all, is compared with the working directory,
+/// or with the index if `--staged` was provided.
+pub fn get_diff_range(
+    range: Option<&str>,
+    repo: &Repository,
+    staged: bool,
+) -> Result<DiffRange, NomadError> {
+    let resolve = |revision: &str| -> Result<Oid, NomadError> {
+        Ok(get_revision_commit(
+            repo,
+            if revision.is_empty() {
+                "HEAD"
+            } else {
+                revision
+            },
+        )?
+        .id())
+    };
+
+    let range = range.unwrap_or("HEAD");
+    let sides = match range.split_once("...") {
+        Some((old, new)) => {
+            let new = resolve(new)?;
+            Some((repo.merge_base(resolve(old)?, new)?, new))
+        }
+        None => match range.split_once("..") {
+            Some((old, new)) => Some((resolve(old)?, resolve(new)?)),
+            None => None,
+        },
+    };
+
+    match (sides, staged) {
+        (Some(_), true) => Err(NomadError::Error(anyhow!(
+            "--staged compares a single revision with the index and cannot be used with {range}"
+        ))),
+        (Some((old, new)), false) => Ok(DiffRange::Commits {
+            old: old.to_string(),
+            new: new.to_string(),
+        }),
+        (None, true) => Ok(DiffRange::Staged {
+            old: resolve(range)?.to_string(),
+        }),
+        (None, false) => Ok(DiffRange::WorkingDirectory {
+            old: resolve(range)?.to_string(),
+        }),
+    }
+}
+
+/// Display the files that changed within the diff range in tree form.
+///
+/// Each file is marked with its change and the number of lines that were added and
+/// deleted. The diff range is stored with the labels so that the diffs may be
+/// displayed for the labeled items.
+pub fn display_diff_tree(
+    args: &git::DiffOptions,
+    diff_range: &DiffRange,
+    nomad_style: &NomadStyle,
+    repo: &Repository,
+    target_directory: &str,
+) -> Result<(), NomadError> {
+    let global_args = GlobalArgs {
+        export: None,
+        export_format: ExportFormat::Text,
+        graph: GraphArgs {
+            graph_depth: None,
+            graph_max_nodes: None,
+        },
+        json: false,
+        labels: LabelArgs {
+            all_labels: args.labels.all_labels,
+            label_directories: args.labels.label_directories,
+            numbers: args.labels.numbers,
+        },
+        meta: MetaArgs::default(),
+        modifiers: ModifierArgs::default(),
+        regex: RegexArgs {
+            grep: None,
+            pattern: None,
+        },
+        style: StyleArgs {
+            no_colors: args.style.no_colors,
+            no_git: args.style.no_git,
+            no_icons: args.style.no_icons,
+            plain: args.style.plain,
+        },
+        statistics: false,
+        stream: false,
+    };
+
+    let diff = get_repo_diffs(diff_range, repo)?;
+    let repo_root = get_repo_root(repo)?;
+
+    let file_changes = get_line_changes(&diff, &repo_root)?;
+
+    let mut found_items: Vec<FoundItem> = Vec::new();
+    for delta in diff.deltas() {
+        let path = repo_root.join(get_delta_path(&delta));
+        if !path.starts_with(target_directory) {
+            continue;
+        }
+
+        found_items.push(FoundItem {
+            line_matches: Vec::new(),
+            marker: get_delta_marker(&global_args.style, delta.status(), nomad_style),
+            matched: None,
+            path: path.to_str().unwrap_or("?").to_string(),
+            status: None,
+        });
+    }
+
+    let range_description = match diff_range {
+        DiffRange::Commits { old, new } => format!("{}..{}", &old[..7], &new[..7]),
+        DiffRange::Staged { old } => format!("{} and the index", &old[..7]),
+        DiffRange::WorkingDirectory { old } => format!("{} and the working directory", &old[..7]),
+    };
+
+    let is_plain = args.style.plain || args.style.no_colors;
+    if found_items.is_empty() {
+        let message = format!("No changes between {range_description}.");
+        println!(
+            "\n{}\n",
+            if is_plain {
+                message
+            } else {
+                Colour::Green.bold().paint(message).to_string()
+            }
+        );
+
+        return Ok(());
+    }
+
+    println!(
+        "\nChanges between {}",
+        if is_plain {
+            range_description
+        } else {
+            Colour::Fixed(172)
+                .bold()
+                .paint(range_description)
+                .to_string()
+        }
+    );
+
+    found_items.sort_by(|first, second| Path::new(&first.path).cmp(Path::new(&second.path)));
+
+    let mut transformed_items = found_items.transform(target_directory)?;
+    set_line_changes(&mut transformed_items, &file_changes, target_directory);
+
+    sort_items(
+        transformed_items,
+        &get_sort_options(&global_args, nomad_style),
+        target_directory,
+    )
+    .to_tree(
+        &global_args,
+        NomadMode::GitStatus,
+        nomad_style,
+        target_directory,
+    )?;
+
+    store_diff_range(diff_range)
+}
+
+/// Get the number of lines that were added and deleted in each changed file by
+/// combining the staged and unstaged diffs. Every line in an untracked file counts
+/// as an added line.
+///
+/// Files are keyed by their absolute paths, just like the Git statuses.
+pub fn get_numstat(repo: &Repository) -> Result<HashMap<String, LineChanges>, NomadError> {
+    let repo_root = get_repo_root(repo)?;
+
+    // `HEAD` does not point to a tree before the first commit, so everything in the
+    // index is added.
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let index = repo.index()?;
+
+    let mut diff_options = git2::DiffOptions::new();
+    diff_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+
+    let staged_diff = repo.diff_tree_to_index(head_tree.as_ref(), Some(&index), None)?;
+    let unstaged_diff = repo.diff_index_to_workdir(Some(&index), Some(&mut diff_options))?;
+
+    let mut numstat = get_line_changes(&staged_diff, &repo_root)?;
+    for (path, line_changes) in get_line_changes(&unstaged_diff, &repo_root)? {
+        let total = numstat.entry(path).or_default();
+        total.added += line_changes.added;
+        total.deleted += line_changes.deleted;
+    }
+
+    Ok(numstat)
+}
+
+/// Get the number of lines that were added and deleted in each file in the diff,
+/// keyed by the file's absolute path.
+fn get_line_changes(
+    diff: &Diff,
+    repo_root: &Path,
+) -> Result<HashMap<String, LineChanges>, NomadError> {
+    let mut line_changes = HashMap::new();
+
+    for (index, delta) in diff.deltas().enumerate() {
+        let (_, added, deleted) = match Patch::from_diff(diff, index)? {
+            Some(patch) => patch.line_stats()?,
+            None => (0, 0, 0),
+        };
+
+        line_changes.insert(
+            repo_root
+                .join(get_delta_path(&delta))
+                .to_str()
+                .unwrap_or("?")
+                .to_string(),
+            LineChanges { added, deleted },
+        );
+    }
+
+    Ok(line_changes)
+}
+
+/// Get the path of the file in the delta. Deleted files only have an old path.
+fn get_delta_path<'a>(delta: &'a DiffDelta) -> &'a Path {
+    delta
+        .new_file()
+        .path()
+        .or_else(|| delta.old_file().path())
+        .unwrap_or_else(|| Path::new("?"))
+}
+
+/// Add the diff range to the labels that were stored after building the tree.
+fn store_diff_range(diff_range: &DiffRange) -> Result<(), NomadError> {
+    let mut contents = get_deserialized_json()?;
+    contents.diff = Some(diff_range.clone());
+
+    write_to_json(&mut get_json_file(false)?, serde_json::to_value(contents)?)
+}
+
+/// Get the marker that corresponds with the change that was made to the file, ie.
+/// `A` for added files.
+fn get_delta_marker(args: &StyleArgs, delta: Delta, nomad_style: &NomadStyle) -> Option<String> {
+    let (color, marker) = match delta {
+        Delta::Added | Delta::Copied | Delta::Untracked => {
+            (nomad_style.git.staged_added_color, "A")
+        }
+        Delta::Conflicted => (nomad_style.git.conflicted_color, "C"),
+        Delta::Deleted => (nomad_style.git.deleted_color, "D"),
+        Delta::Modified => (nomad_style.git.modified_color, "M"),
+        Delta::Renamed => (nomad_style.git.renamed_color, "R"),
+        Delta::Typechange => (nomad_style.git.typechanged_color, "T"),
+        _ => return None,
+    };
+
+    if args.no_colors {
+        Some(marker.to_string())
+    } else {
+        Some(color.paint(marker).to_string())
+    }
+}
+
 /// Use `bat` to display Git diffs.
 pub fn bat_diffs(
     diff: Diff,
@@ -128,35 +431,6 @@ fn get_diffs(
     let mut formatted_diffs: Vec<(String, String)> = Vec::new();
 
     diff.print(DiffFormat::Patch, |delta, hunk, line| {
-        let old_syntax = SYNTAX_SET
-            .find_syntax_by_extension(
-                delta
-                    .old_file()
-                    .path()
-                    .unwrap_or_else(|| Path::new("?"))
-                    .extension()
-                    .unwrap_or_else(|| OsStr::new("?"))
-                    .to_str()
-                    .unwrap_or("?"),
-            )
-            .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
-        let new_syntax = SYNTAX_SET
-            .find_syntax_by_extension(
-                delta
-                    .new_file()
-                    .path()
-                    .unwrap_or_else(|| Path::new("?"))
-                    .extension()
-                    .unwrap_or_else(|| OsStr::new("?"))
-                    .to_str()
-                    .unwrap_or("?"),
-            )
-            .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
-        let mut old_highlighter =
-            HighlightLines::new(old_syntax, &THEME_SET.themes["base16-ocean.dark"]);
-        let mut new_highlighter =
-            HighlightLines::new(new_syntax, &THEME_SET.themes["base16-ocean.dark"]);
-
         if filename.is_empty() {
             let (new_filename, old_filename) = get_new_old_filenames(&delta);
             new_file = new_filename;
@@ -173,100 +447,43 @@ fn get_diffs(
             match line.origin() {
                 // Format the file or hunk header for better clarity.
                 'H' | 'F' => {
-                    let number_line = if hunk.old_start() != hunk.new_start() {
-                        format!(
-                            "\n@@ {} {} {} {}",
-                            Colour::White.bold().paint("Line"),
-                            Colour::Red.bold().paint(hunk.old_start().to_string()),
-                            Colour::White.bold().paint("==>"),
-                            Colour::Green.bold().paint(hunk.new_start().to_string())
-                        )
-                    } else {
-                        Colour::White
-                            .bold()
-                            .paint(format!("\n@@ Line {}", hunk.old_start()))
-                            .to_string()
-                    };
-
-                    let num_lines = if hunk.old_lines() != hunk.new_lines() {
-                        format!(
-                            "{} {} {} {}",
-                            Colour::White.bold().paint("# of lines:"),
-                            Colour::Red.bold().paint(hunk.old_lines().to_string()),
-                            Colour::White.bold().paint("==>"),
-                            Colour::Green.bold().paint(hunk.new_lines().to_string())
-                        )
-                    } else {
-                        Colour::White
-                            .bold()
-                            .paint(format!("# of lines: {}", hunk.old_lines()))
-                            .to_string()
-                    };
-
-                    content.push(format!(
-                        "{number_line} {} {num_lines}\n\n",
-                        Colour::White.bold().paint("|")
+                    content.push(format_hunk_header(
+                        hunk.old_start(),
+                        hunk.old_lines(),
+                        hunk.new_start(),
+                        hunk.new_lines(),
                     ));
                 }
                 // Otherwise format the line based on the Git Delta's status.
                 _ => {
-                    let content_text = from_utf8(line.content()).unwrap_or("?");
-
-                    let highlighted_line = match delta.status() {
+                    match delta.status() {
                         Delta::Added => {
                             current_delta = Some(Delta::Added);
                             added_lines += 1;
-
-                            highlight_line(Some(*GREEN), content_text, &mut new_highlighter, true)
-                        }
-                        Delta::Conflicted => {
-                            current_delta = Some(Delta::Conflicted);
-
-                            highlight_line(Some(*ORANGE), content_text, &mut old_highlighter, true)
                         }
+                        Delta::Conflicted => current_delta = Some(Delta::Conflicted),
                         Delta::Deleted => {
                             current_delta = Some(Delta::Deleted);
                             deleted_lines += 1;
-
-                            highlight_line(Some(*RED), content_text, &mut old_highlighter, true)
                         }
                         Delta::Modified => {
-                            let (background_color, mut modified_highlighter, paint_background) =
-                                match line.origin() {
-                                    '+' | '>' => {
-                                        added_lines += 1;
-
-                                        (Some(*GREEN), new_highlighter, true)
-                                    }
-                                    '-' | '<' => {
-                                        deleted_lines += 1;
-
-                                        (Some(*RED), old_highlighter, true)
-                                    }
-                                    _ => (None, old_highlighter, false),
-                                };
+                            match line.origin() {
+                                '+' | '>' => added_lines += 1,
+                                '-' | '<' => deleted_lines += 1,
+                                _ => {}
+                            }
 
                             current_delta = Some(Delta::Modified);
-
-                            highlight_line(
-                                background_color,
-                                content_text,
-                                &mut modified_highlighter,
-                                paint_background,
-                            )
-                        }
-                        Delta::Renamed | Delta::Typechange => {
-                            current_delta = Some(Delta::Renamed);
-
-                            highlight_line(Some(*GREEN), content_text, &mut new_highlighter, true)
                         }
-                        _ => highlight_line(None, content_text, &mut old_highlighter, false),
-                    };
+                        Delta::Renamed | Delta::Typechange => current_delta = Some(Delta::Renamed),
+                        _ => {}
+                    }
 
-                    content.push(format!(
-                        "{} {highlighted_line}",
-                        colorize_origin(line.origin())
-                    ))
+                    content.push(format_diff_line(
+                        &delta,
+                        line.origin(),
+                        from_utf8(line.content()).unwrap_or("?"),
+                    ));
                 }
             }
 
@@ -321,6 +538,18 @@ fn get_diffs(
 
                 content.clear();
                 filename.clear();
+            } else {
+                // Files without changed lines, such as renamed files, do not contain
+                // any hunks, so the name of the file being visited is set here.
+                let (new_filename, old_filename) = get_new_old_filenames(&delta);
+                new_file = new_filename;
+                old_file = old_filename;
+
+                filename = if old_file == new_file {
+                    new_file.clone()
+                } else {
+                    format!("{old_file} ==> {new_file}")
+                };
             }
 
             added_lines = 0;
@@ -359,6 +588,112 @@ fn get_diffs(
     Ok(formatted_diffs)
 }
 
+/// Format the header of a hunk for better clarity. The header contains the lines the
+/// hunk starts at and the number of lines it spans on each side of the diff.
+pub fn format_hunk_header(
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+) -> String {
+    let number_line = if old_start != new_start {
+        format!(
+            "\n@@ {} {} {} {}",
+            Colour::White.bold().paint("Line"),
+            Colour::Red.bold().paint(old_start.to_string()),
+            Colour::White.bold().paint("==>"),
+            Colour::Green.bold().paint(new_start.to_string())
+        )
+    } else {
+        Colour::White
+            .bold()
+            .paint(format!("\n@@ Line {}", old_start))
+            .to_string()
+    };
+
+    let num_lines = if old_lines != new_lines {
+        format!(
+            "{} {} {} {}",
+            Colour::White.bold().paint("# of lines:"),
+            Colour::Red.bold().paint(old_lines.to_string()),
+            Colour::White.bold().paint("==>"),
+            Colour::Green.bold().paint(new_lines.to_string())
+        )
+    } else {
+        Colour::White
+            .bold()
+            .paint(format!("# of lines: {}", old_lines))
+            .to_string()
+    };
+
+    format!(
+        "{number_line} {} {num_lines}\n\n",
+        Colour::White.bold().paint("|")
+    )
+}
+
+/// Format a line of a diff based on the Git Delta's status. The line is syntax
+/// highlighted based on the extension of the file, and added or deleted lines are
+/// painted with a background color.
+pub fn format_diff_line(delta: &DiffDelta, origin: char, content_text: &str) -> String {
+    let old_syntax = SYNTAX_SET
+        .find_syntax_by_extension(
+            delta
+                .old_file()
+                .path()
+                .unwrap_or_else(|| Path::new("?"))
+                .extension()
+                .unwrap_or_else(|| OsStr::new("?"))
+                .to_str()
+                .unwrap_or("?"),
+        )
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+    let new_syntax = SYNTAX_SET
+        .find_syntax_by_extension(
+            delta
+                .new_file()
+                .path()
+                .unwrap_or_else(|| Path::new("?"))
+                .extension()
+                .unwrap_or_else(|| OsStr::new("?"))
+                .to_str()
+                .unwrap_or("?"),
+        )
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+    let mut old_highlighter =
+        HighlightLines::new(old_syntax, &THEME_SET.themes["base16-ocean.dark"]);
+    let mut new_highlighter =
+        HighlightLines::new(new_syntax, &THEME_SET.themes["base16-ocean.dark"]);
+
+    let highlighted_line = match delta.status() {
+        Delta::Added => highlight_line(Some(*GREEN), content_text, &mut new_highlighter, true),
+        Delta::Conflicted => {
+            highlight_line(Some(*ORANGE), content_text, &mut old_highlighter, true)
+        }
+        Delta::Deleted => highlight_line(Some(*RED), content_text, &mut old_highlighter, true),
+        Delta::Modified => {
+            let (background_color, mut modified_highlighter, paint_background) = match origin {
+                '+' | '>' => (Some(*GREEN), new_highlighter, true),
+                '-' | '<' => (Some(*RED), old_highlighter, true),
+                _ => (None, old_highlighter, false),
+            };
+
+            highlight_line(
+                background_color,
+                content_text,
+                &mut modified_highlighter,
+                paint_background,
+            )
+        }
+        Delta::Renamed | Delta::Typechange => {
+            highlight_line(Some(*GREEN), content_text, &mut new_highlighter, true)
+        }
+        _ => highlight_line(None, content_text, &mut old_highlighter, false),
+    };
+
+    format!("{} {highlighted_line}", colorize_origin(origin))
+}
+
 /// Get the new and old filenames from the DiffDelta.
 fn get_new_old_filenames(delta: &DiffDelta) -> (String, String) {
     (
@@ -498,13 +833,14 @@ fn get_formatted_filename(
     }
 }
 
-/// Get Git diff statistics by comparing the HEAD and index.
+/// Get Git diff statistics by comparing a tree and the index. Every staged file is
+/// counted as added if there is no tree to compare against.
 pub fn get_diff_stats(
     index: &mut Index,
-    old_tree: &Tree,
+    old_tree: Option<&Tree>,
     repo: &Repository,
 ) -> (Option<usize>, Option<usize>, Option<usize>) {
-    if let Ok(diff) = repo.diff_tree_to_index(Some(old_tree), Some(index), None) {
+    if let Ok(diff) = repo.diff_tree_to_index(old_tree, Some(index), None) {
         if let Ok(diff_stats) = diff.stats() {
             (
                 Some(diff_stats.files_changed()),
@@ -518,3 +854,114 @@ pub fn get_diff_stats(
         (None, None, None)
     }
 }
+
+#[cfg(test)]
+mod test_diff {
+    use super::*;
+    use crate::git::utils::test_utils::{commit_files, init_repo};
+
+    /// Create a repository where `main` and `side` both branch off of `base`. `HEAD`
+    /// is at `main`.
+    fn diverged_repo() -> (tempfile::TempDir, Repository, Oid, Oid, Oid) {
+        let (directory, repo) = init_repo();
+
+        let base = commit_files(&repo, &[("a.txt", "a\n")], "Add a.txt", &[]);
+        let side = commit_files(&repo, &[("b.txt", "b\n")], "Add b.txt", &[base]);
+        let main = commit_files(&repo, &[("c.txt", "c\n")], "Add c.txt", &[base]);
+
+        (directory, repo, base, main, side)
+    }
+
+    #[test]
+    fn test_get_diff_range_two_dots() {
+        let (_directory, repo, _, main, side) = diverged_repo();
+
+        assert_eq!(
+            get_diff_range(Some(&format!("{main}..{side}")), &repo, false).unwrap(),
+            DiffRange::Commits {
+                old: main.to_string(),
+                new: side.to_string(),
+            }
+        );
+
+        // Either side defaults to `HEAD`.
+        assert_eq!(
+            get_diff_range(Some(&format!("{side}..")), &repo, false).unwrap(),
+            DiffRange::Commits {
+                old: side.to_string(),
+                new: main.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_diff_range_three_dots() {
+        let (_directory, repo, base, main, side) = diverged_repo();
+
+        // The new side is compared with the commit both sides branched off of.
+        assert_eq!(
+            get_diff_range(Some(&format!("{main}...{side}")), &repo, false).unwrap(),
+            DiffRange::Commits {
+                old: base.to_string(),
+                new: side.to_string(),
+            }
+        );
+        assert_eq!(
+            get_diff_range(Some(&format!("...{side}")), &repo, false).unwrap(),
+            DiffRange::Commits {
+                old: base.to_string(),
+                new: side.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_diff_range_single_revision() {
+        let (_directory, repo, base, main, _) = diverged_repo();
+
+        assert_eq!(
+            get_diff_range(Some(&base.to_string()), &repo, false).unwrap(),
+            DiffRange::WorkingDirectory {
+                old: base.to_string(),
+            }
+        );
+        assert_eq!(
+            get_diff_range(None, &repo, false).unwrap(),
+            DiffRange::WorkingDirectory {
+                old: main.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_diff_range_staged() {
+        let (_directory, repo, base, main, side) = diverged_repo();
+
+        assert_eq!(
+            get_diff_range(None, &repo, true).unwrap(),
+            DiffRange::Staged {
+                old: main.to_string(),
+            }
+        );
+        assert_eq!(
+            get_diff_range(Some(&base.to_string()), &repo, true).unwrap(),
+            DiffRange::Staged {
+                old: base.to_string(),
+            }
+        );
+
+        // `--staged` only compares a single revision with the index.
+        assert!(get_diff_range(Some(&format!("{main}..{side}")), &repo, true).is_err());
+        assert!(get_diff_range(Some(&format!("{main}...{side}")), &repo, true).is_err());
+    }
+
+    #[test]
+    fn test_get_diff_range_invalid() {
+        let (_directory, repo, _, main, _) = diverged_repo();
+
+        assert!(get_diff_range(Some("missing"), &repo, false).is_err());
+        assert!(get_diff_range(Some(&format!("{main}..missing")), &repo, false).is_err());
+        assert!(get_diff_range(Some(&format!("missing...{main}")), &repo, false).is_err());
+        assert!(get_diff_range(Some("missing"), &repo, true).is_err());
+    }
+}
@@ -0,0 +1,736 @@
+//! Interactively stage the hunks of the working tree diff.
+
+use std::{
+    collections::VecDeque,
+    io::{stdin, stdout, Write},
+    path::Path,
+    str::from_utf8,
+};
+
+use ansi_term::Colour;
+use bat::{Input, PagingMode, PrettyPrinter, WrappingMode};
+use git2::{ApplyLocation, Diff, DiffDelta, Patch, Repository};
+
+use super::{
+    diff::{format_diff_line, format_hunk_header},
+    utils::get_repo_root,
+};
+use crate::{
+    cli::Args,
+    errors::NomadError,
+    style::models::NomadStyle,
+    utils::search::{indiscriminate_search, SearchMode},
+};
+
+/// Contains variants for the choices that may be made for each hunk.
+enum HunkChoice {
+    /// Stage the hunk.
+    Stage,
+    /// Do not stage the hunk.
+    Skip,
+    /// Split the hunk into smaller hunks.
+    Split,
+    /// Do not stage the hunk or any of the remaining hunks.
+    Quit,
+}
+
+/// A line within a hunk.
+struct HunkLine {
+    /// The origin of the line, ie. `+` for added lines or `-` for deleted lines.
+    origin: char,
+    /// The raw content of the line.
+    content: Vec<u8>,
+    /// Whether the line will be staged. Only applies to added or deleted lines.
+    staged: bool,
+}
+
+/// A hunk from a file's working tree diff.
+struct Hunk {
+    /// The line the hunk starts at in the index.
+    old_start: u32,
+    /// The number of lines the hunk spans in the index.
+    old_lines: u32,
+    /// The line the hunk starts at in the working tree.
+    new_start: u32,
+    /// The number of lines the hunk spans in the working tree.
+    new_lines: u32,
+    /// The lines within the hunk.
+    lines: Vec<HunkLine>,
+}
+
+/// A range of lines within a hunk that is displayed and staged as a single unit.
+/// Splitting a hunk creates a range for each group of changed lines. Neighboring
+/// ranges share the context lines between them.
+#[derive(Clone, Copy)]
+struct HunkRange {
+    /// The index of the first line in the range.
+    start: usize,
+    /// The index after the last line in the range.
+    end: usize,
+    /// The line the range starts at in the index.
+    old_start: u32,
+    /// The line the range starts at in the working tree.
+    new_start: u32,
+}
+
+/// Walk through each hunk of the working tree diff and interactively choose which
+/// hunks are staged. Only the files matching the item labels are walked if labels
+/// were provided, otherwise every modified file is walked. Untracked files are not
+/// walked since they do not contain a diff against the index.
+///
+/// The selected hunks are applied to the index once every hunk has been visited or
+/// the walk has been quit.
+pub fn stage_hunks(
+    args: &Args,
+    item_labels: &[String],
+    nomad_style: &NomadStyle,
+    repo: &Repository,
+    target_directory: &str,
+) -> Result<(), NomadError> {
+    let repo_root = get_repo_root(repo)?;
+
+    let mut diff_options = git2::DiffOptions::new();
+    if !item_labels.is_empty() {
+        match indiscriminate_search(
+            args,
+            item_labels,
+            nomad_style,
+            Some(repo),
+            SearchMode::Git,
+            target_directory,
+        ) {
+            Some(found_items) if !found_items.is_empty() => {
+                for item in found_items {
+                    diff_options.pathspec(
+                        Path::new(&item)
+                            .strip_prefix(&repo_root)
+                            .unwrap_or_else(|_| Path::new(&item)),
+                    );
+                }
+
+                diff_options.disable_pathspec_match(true);
+            }
+            _ => {
+                println!("{}\n", Colour::Red.bold().paint("No hunks were staged!"));
+
+                return Ok(());
+            }
+        }
+    }
+
+    let diff = repo.diff_index_to_workdir(None, Some(&mut diff_options))?;
+
+    let mut staged_patch: Vec<u8> = Vec::new();
+    let mut staged_hunks = 0;
+    let mut quit = false;
+
+    for delta_index in 0..diff.deltas().len() {
+        if quit {
+            break;
+        }
+
+        // Binary files and files that only changed modes do not contain any hunks.
+        let mut patch = match Patch::from_diff(&diff, delta_index)? {
+            Some(patch) if patch.num_hunks() > 0 => patch,
+            _ => continue,
+        };
+
+        let mut hunks = get_hunks(&patch)?;
+        let mut ranges = hunks
+            .iter()
+            .enumerate()
+            .map(|(hunk_index, hunk)| {
+                (
+                    hunk_index,
+                    HunkRange {
+                        start: 0,
+                        end: hunk.lines.len(),
+                        old_start: hunk.old_start,
+                        new_start: hunk.new_start,
+                    },
+                )
+            })
+            .collect::<VecDeque<(usize, HunkRange)>>();
+
+        while let Some((hunk_index, range)) = ranges.pop_front() {
+            let split_ranges = split_range(&hunks[hunk_index], range);
+
+            display_hunk(
+                &patch.delta(),
+                &hunks[hunk_index],
+                range,
+                hunk_index + 1,
+                hunks.len(),
+            )?;
+
+            match prompt_hunk_choice(split_ranges.len() > 1)? {
+                HunkChoice::Stage => {
+                    for line in hunks[hunk_index].lines[range.start..range.end].iter_mut() {
+                        line.staged = matches!(line.origin, '+' | '-');
+                    }
+
+                    staged_hunks += 1;
+                }
+                HunkChoice::Skip => {}
+                HunkChoice::Split => {
+                    println!(
+                        "{}",
+                        Colour::Fixed(172)
+                            .bold()
+                            .paint(format!("Split into {} hunks.", split_ranges.len()))
+                    );
+
+                    for split_range in split_ranges.into_iter().rev() {
+                        ranges.push_front((hunk_index, split_range));
+                    }
+                }
+                HunkChoice::Quit => {
+                    quit = true;
+                    break;
+                }
+            }
+        }
+
+        if hunks
+            .iter()
+            .any(|hunk| hunk.lines.iter().any(|line| line.staged))
+        {
+            staged_patch.extend(get_staged_patch(&mut patch, &hunks)?);
+        }
+    }
+
+    if staged_hunks > 0 {
+        let staged_diff = Diff::from_buffer(&staged_patch)?;
+        repo.apply(&staged_diff, ApplyLocation::Index, None)?;
+
+        println!(
+            "\nStaged {} {}\n",
+            Colour::Green.bold().paint(format!("{staged_hunks}")),
+            if staged_hunks == 1 { "hunk" } else { "hunks" }
+        );
+    } else {
+        println!("\n{}\n", Colour::Red.bold().paint("No hunks were staged!"));
+    }
+
+    Ok(())
+}
+
+/// Get the hunks and their lines from a file's patch.
+fn get_hunks(patch: &Patch) -> Result<Vec<Hunk>, NomadError> {
+    let mut hunks = Vec::new();
+
+    for hunk_index in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_index)?;
+
+        let mut lines = Vec::new();
+        for line_index in 0..line_count {
+            let line = patch.line_in_hunk(hunk_index, line_index)?;
+
+            lines.push(HunkLine {
+                origin: line.origin(),
+                content: line.content().to_vec(),
+                staged: false,
+            });
+        }
+
+        hunks.push(Hunk {
+            old_start: hunk.old_start(),
+            old_lines: hunk.old_lines(),
+            new_start: hunk.new_start(),
+            new_lines: hunk.new_lines(),
+            lines,
+        });
+    }
+
+    Ok(hunks)
+}
+
+/// Count the number of lines the lines span in the index and in the working tree.
+fn count_lines(lines: &[HunkLine]) -> (u32, u32) {
+    lines
+        .iter()
+        .fold((0, 0), |(old_lines, new_lines), line| match line.origin {
+            ' ' => (old_lines + 1, new_lines + 1),
+            '-' => (old_lines + 1, new_lines),
+            '+' => (old_lines, new_lines + 1),
+            _ => (old_lines, new_lines),
+        })
+}
+
+/// Split a range into a range for each group of added or deleted lines within it.
+/// A range containing a single group cannot be split and is returned as is.
+///
+/// Markers indicating that a line does not end with a newline stay with the line
+/// before them.
+fn split_range(hunk: &Hunk, range: HunkRange) -> Vec<HunkRange> {
+    let is_context = |line: &HunkLine| matches!(line.origin, ' ' | '=');
+
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    let mut index = range.start;
+    while index < range.end {
+        if is_context(&hunk.lines[index]) {
+            index += 1;
+        } else {
+            let group_start = index;
+            while index < range.end && !is_context(&hunk.lines[index]) {
+                index += 1;
+            }
+
+            groups.push((group_start, index));
+        }
+    }
+
+    if groups.len() < 2 {
+        return vec![range];
+    }
+
+    (0..groups.len())
+        .map(|group_index| {
+            let start = match group_index {
+                0 => range.start,
+                _ => groups[group_index - 1].1,
+            };
+            let end = match groups.get(group_index + 1) {
+                Some((next_start, _)) => *next_start,
+                None => range.end,
+            };
+            let (old_offset, new_offset) = count_lines(&hunk.lines[range.start..start]);
+
+            HunkRange {
+                start,
+                end,
+                old_start: range.old_start + old_offset,
+                new_start: range.new_start + new_offset,
+            }
+        })
+        .collect()
+}
+
+/// Syntax highlight the lines within the range and display them with `bat`.
+fn display_hunk(
+    delta: &DiffDelta,
+    hunk: &Hunk,
+    range: HunkRange,
+    hunk_number: usize,
+    total_hunks: usize,
+) -> Result<(), NomadError> {
+    let (old_lines, new_lines) = count_lines(&hunk.lines[range.start..range.end]);
+
+    let mut content = vec![format_hunk_header(
+        range.old_start,
+        old_lines,
+        range.new_start,
+        new_lines,
+    )];
+    for line in hunk.lines[range.start..range.end].iter() {
+        content.push(format_diff_line(
+            delta,
+            line.origin,
+            from_utf8(&line.content).unwrap_or("?"),
+        ));
+    }
+    let content = content.join("");
+
+    let filename = format!(
+        "| {} | {} |",
+        delta
+            .new_file()
+            .path()
+            .unwrap_or_else(|| Path::new("?"))
+            .display(),
+        Colour::Fixed(172)
+            .bold()
+            .paint(format!("HUNK {hunk_number}/{total_hunks}"))
+    );
+
+    PrettyPrinter::new()
+        .grid(true)
+        .header(true)
+        .input(Input::from_bytes(content.as_bytes()).name(filename))
+        .paging_mode(PagingMode::Never)
+        .rule(true)
+        .true_color(true)
+        .wrapping_mode(WrappingMode::Character)
+        .print()?;
+
+    Ok(())
+}
+
+/// Prompt for the choice to make for the displayed hunk until a valid choice is
+/// entered. The walk is quit if the input is closed.
+fn prompt_hunk_choice(can_split: bool) -> Result<HunkChoice, NomadError> {
+    let choices = if can_split { "y,n,s,q,?" } else { "y,n,q,?" };
+
+    loop {
+        print!(
+            "{} ",
+            Colour::Blue
+                .bold()
+                .paint(format!("Stage this hunk [{choices}]?"))
+        );
+        stdout().flush()?;
+
+        let mut input = String::new();
+        if stdin().read_line(&mut input)? == 0 {
+            return Ok(HunkChoice::Quit);
+        }
+
+        match input.trim() {
+            "y" => return Ok(HunkChoice::Stage),
+            "n" => return Ok(HunkChoice::Skip),
+            "s" if can_split => return Ok(HunkChoice::Split),
+            "q" => return Ok(HunkChoice::Quit),
+            "s" => println!(
+                "{}",
+                Colour::Red
+                    .bold()
+                    .paint("This hunk cannot be split any further!")
+            ),
+            _ => {
+                println!("y - stage this hunk");
+                println!("n - do not stage this hunk");
+                if can_split {
+                    println!("s - split this hunk into smaller hunks");
+                }
+                println!("q - quit; do not stage this hunk or any of the remaining hunks");
+                println!("? - print help");
+            }
+        }
+    }
+}
+
+/// Build a patch containing the staged lines of a file's hunks.
+///
+/// Added lines that are not staged are left out, and deleted lines that are not
+/// staged are kept as context lines. The hunk headers are recounted to match, and
+/// each hunk's start in the working tree is shifted by the lines that were left out
+/// of the hunks before it.
+fn get_staged_patch(patch: &mut Patch, hunks: &[Hunk]) -> Result<Vec<u8>, NomadError> {
+    let buffer = patch.to_buf()?;
+    let header_end = buffer
+        .windows(4)
+        .position(|window| window == b"\n@@ ")
+        .map_or(buffer.len(), |position| position + 1);
+
+    let mut staged_patch = buffer[..header_end].to_vec();
+    let mut skipped_lines: i64 = 0;
+
+    for hunk in hunks.iter() {
+        if !hunk.lines.iter().any(|line| line.staged) {
+            skipped_lines += hunk.new_lines as i64 - hunk.old_lines as i64;
+            continue;
+        }
+
+        let mut staged_lines: Vec<u8> = Vec::new();
+        let (mut old_lines, mut new_lines) = (0, 0);
+        let mut kept_previous = true;
+
+        for line in hunk.lines.iter() {
+            match (line.origin, line.staged) {
+                (' ', _) | ('-', false) => {
+                    staged_lines.push(b' ');
+                    old_lines += 1;
+                    new_lines += 1;
+                }
+                ('-', true) => {
+                    staged_lines.push(b'-');
+                    old_lines += 1;
+                }
+                ('+', true) => {
+                    staged_lines.push(b'+');
+                    new_lines += 1;
+                }
+                ('+', false) => {
+                    kept_previous = false;
+                    continue;
+                }
+                // Markers indicating that a line does not end with a newline.
+                _ => {
+                    if kept_previous {
+                        staged_lines.extend(&line.content);
+                    }
+
+                    continue;
+                }
+            }
+
+            staged_lines.extend(&line.content);
+            kept_previous = true;
+        }
+
+        staged_patch.extend(
+            format!(
+                "@@ -{},{} +{},{} @@\n",
+                hunk.old_start,
+                old_lines,
+                hunk.new_start as i64 - skipped_lines,
+                new_lines
+            )
+            .as_bytes(),
+        );
+        staged_patch.extend(staged_lines);
+
+        skipped_lines += hunk.new_lines as i64 - new_lines as i64;
+    }
+
+    Ok(staged_patch)
+}
+
+#[cfg(test)]
+mod test_hunks {
+    use super::*;
+
+    /// Create a patch between the contents of a file in the index and in the working
+    /// tree.
+    fn create_patch<'a>(old: &'a str, new: &'a str) -> Patch<'a> {
+        Patch::from_buffers(
+            old.as_bytes(),
+            Some(Path::new("file")),
+            new.as_bytes(),
+            Some(Path::new("file")),
+            None,
+        )
+        .unwrap()
+    }
+
+    /// Get the lines `1` through `total`.
+    fn numbered_lines(total: usize) -> Vec<String> {
+        (1..=total).map(|number| format!("{number}\n")).collect()
+    }
+
+    /// Stage the added and deleted lines within the range.
+    fn stage_range(hunk: &mut Hunk, range: HunkRange) {
+        for line in hunk.lines[range.start..range.end].iter_mut() {
+            line.staged = matches!(line.origin, '+' | '-');
+        }
+    }
+
+    /// Get the hunk headers and lines of the staged patch.
+    fn get_staged_hunks(patch: &mut Patch, hunks: &[Hunk]) -> Vec<String> {
+        let staged_patch = get_staged_patch(patch, hunks).unwrap();
+
+        // The staged patch has to be a valid patch for it to be applied to the index.
+        Diff::from_buffer(&staged_patch).unwrap();
+
+        String::from_utf8(staged_patch)
+            .unwrap()
+            .lines()
+            .skip_while(|line| !line.starts_with("@@"))
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    /// Replace line 2 with two lines and change line 8, which creates a single hunk
+    /// containing two groups of changes.
+    fn get_split_contents() -> (String, String) {
+        let old = numbered_lines(10);
+        let mut new = old.clone();
+        new[7] = "8x\n".to_string();
+        new.splice(1..2, ["2a\n".to_string(), "2b\n".to_string()]);
+
+        (old.join(""), new.join(""))
+    }
+
+    /// Get the range spanning every line in the hunk.
+    fn full_range(hunk: &Hunk) -> HunkRange {
+        HunkRange {
+            start: 0,
+            end: hunk.lines.len(),
+            old_start: hunk.old_start,
+            new_start: hunk.new_start,
+        }
+    }
+
+    #[test]
+    fn test_split_range_offsets() {
+        let (old, new) = get_split_contents();
+        let patch = create_patch(&old, &new);
+        let hunks = get_hunks(&patch).unwrap();
+        assert_eq!(hunks.len(), 1);
+
+        let split_ranges = split_range(&hunks[0], full_range(&hunks[0]));
+        let ranges = split_ranges
+            .iter()
+            .map(|range| (range.start, range.end, range.old_start, range.new_start))
+            .collect::<Vec<(usize, usize, u32, u32)>>();
+
+        // The second range starts at line 3 in the index, which is line 4 in the working
+        // tree after line 2 was replaced with two lines.
+        assert_eq!(ranges, vec![(0, 9, 1, 1), (4, 13, 3, 4)]);
+
+        let (old_lines, new_lines) =
+            count_lines(&hunks[0].lines[split_ranges[1].start..split_ranges[1].end]);
+        assert_eq!(
+            format_hunk_header(
+                split_ranges[1].old_start,
+                old_lines,
+                split_ranges[1].new_start,
+                new_lines
+            ),
+            format_hunk_header(3, 8, 4, 8)
+        );
+    }
+
+    #[test]
+    fn test_split_range_single_group() {
+        let patch = create_patch("1\n2\n3\n", "1\n2x\n3\n");
+        let hunks = get_hunks(&patch).unwrap();
+
+        let range = full_range(&hunks[0]);
+        let split_ranges = split_range(&hunks[0], range);
+
+        assert_eq!(split_ranges.len(), 1);
+        assert_eq!(split_ranges[0].start, range.start);
+        assert_eq!(split_ranges[0].end, range.end);
+    }
+
+    #[test]
+    fn test_staged_patch_first_split_range() {
+        let (old, new) = get_split_contents();
+        let mut patch = create_patch(&old, &new);
+        let mut hunks = get_hunks(&patch).unwrap();
+        let split_ranges = split_range(&hunks[0], full_range(&hunks[0]));
+        stage_range(&mut hunks[0], split_ranges[0]);
+
+        // The unstaged deletion of line 8 is kept as context and the unstaged addition
+        // is left out.
+        assert_eq!(
+            get_staged_hunks(&mut patch, &hunks),
+            vec![
+                "@@ -1,10 +1,11 @@",
+                " 1",
+                "-2",
+                "+2a",
+                "+2b",
+                " 3",
+                " 4",
+                " 5",
+                " 6",
+                " 7",
+                " 8",
+                " 9",
+                " 10"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_staged_patch_second_split_range() {
+        let (old, new) = get_split_contents();
+        let mut patch = create_patch(&old, &new);
+        let mut hunks = get_hunks(&patch).unwrap();
+        let split_ranges = split_range(&hunks[0], full_range(&hunks[0]));
+        stage_range(&mut hunks[0], split_ranges[1]);
+
+        assert_eq!(
+            get_staged_hunks(&mut patch, &hunks),
+            vec![
+                "@@ -1,10 +1,10 @@",
+                " 1",
+                " 2",
+                " 3",
+                " 4",
+                " 5",
+                " 6",
+                " 7",
+                "-8",
+                "+8x",
+                " 9",
+                " 10"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_staged_patch_after_skipped_hunk() {
+        // Add a line after line 2 and change line 18, which creates two hunks.
+        let old = numbered_lines(20);
+        let mut new = old.clone();
+        new[17] = "18x\n".to_string();
+        new.insert(2, "new\n".to_string());
+
+        let (old, new) = (old.join(""), new.join(""));
+        let mut patch = create_patch(&old, &new);
+        let mut hunks = get_hunks(&patch).unwrap();
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(
+            (
+                hunks[1].old_start,
+                hunks[1].old_lines,
+                hunks[1].new_start,
+                hunks[1].new_lines
+            ),
+            (15, 6, 16, 6)
+        );
+
+        let range = full_range(&hunks[1]);
+        stage_range(&mut hunks[1], range);
+
+        // The line added by the skipped hunk is not in the index, so the second hunk
+        // starts at the same line in the index before and after it is applied.
+        assert_eq!(
+            get_staged_hunks(&mut patch, &hunks),
+            vec![
+                "@@ -15,6 +15,6 @@",
+                " 15",
+                " 16",
+                " 17",
+                "-18",
+                "+18x",
+                " 19",
+                " 20"
+            ]
+        );
+
+        let range = full_range(&hunks[0]);
+        stage_range(&mut hunks[0], range);
+
+        let staged_hunks = get_staged_hunks(&mut patch, &hunks);
+        assert_eq!(
+            staged_hunks
+                .iter()
+                .filter(|line| line.starts_with("@@"))
+                .collect::<Vec<&String>>(),
+            vec!["@@ -1,5 +1,6 @@", "@@ -15,6 +16,6 @@"]
+        );
+    }
+
+    #[test]
+    fn test_staged_patch_after_partially_staged_hunk() {
+        // Add lines after lines 2 and 5, which are in the same hunk, and change line 18.
+        let old = numbered_lines(20);
+        let mut new = old.clone();
+        new[17] = "18x\n".to_string();
+        new.insert(5, "b\n".to_string());
+        new.insert(2, "a\n".to_string());
+
+        let (old, new) = (old.join(""), new.join(""));
+        let mut patch = create_patch(&old, &new);
+        let mut hunks = get_hunks(&patch).unwrap();
+        assert_eq!(hunks.len(), 2);
+
+        let split_ranges = split_range(&hunks[0], full_range(&hunks[0]));
+        assert_eq!(split_ranges.len(), 2);
+        assert_eq!(
+            (split_ranges[1].old_start, split_ranges[1].new_start),
+            (3, 4)
+        );
+
+        stage_range(&mut hunks[0], split_ranges[1]);
+        let range = full_range(&hunks[1]);
+        stage_range(&mut hunks[1], range);
+
+        // Only one of the two lines added by the first hunk is staged, so the second
+        // hunk starts one line earlier than it does in the working tree.
+        let staged_hunks = get_staged_hunks(&mut patch, &hunks);
+        assert_eq!(
+            staged_hunks
+                .iter()
+                .filter(|line| line.starts_with("@@"))
+                .collect::<Vec<&String>>(),
+            vec!["@@ -1,8 +1,9 @@", "@@ -15,6 +16,6 @@"]
+        );
+        assert!(!staged_hunks.contains(&"+a".to_string()));
+        assert!(staged_hunks.contains(&"+b".to_string()));
+    }
+}
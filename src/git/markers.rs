@@ -1,43 +1,134 @@
 //! Set Git status markers for items within the tree.
 
 use super::utils::get_repo;
-use crate::{cli::global::StyleArgs, errors::NomadError, style::models::NomadStyle};
+use crate::{
+    cli::global::StyleArgs, errors::NomadError, models::GitStatus, style::models::NomadStyle,
+};
 
+use ansi_term::Style;
 use anyhow::Result;
 use git2::{Repository, Status, StatusOptions, StatusShow};
 
-use std::{collections::HashMap, path::Path};
+use std::{collections::HashMap, path::Path, str::FromStr};
 
-/// Try to extend the `HashMap` containing status markers and their corresponding
+/// Categories of Git statuses that the tree may be filtered by with `--only`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatusFilter {
+    /// Items that contain merge conflicts.
+    Conflicted,
+    /// Items that were deleted, whether or not the deletion is staged.
+    Deleted,
+    /// Items that were modified, whether or not the modification is staged.
+    Modified,
+    /// Items that contain staged changes.
+    Staged,
+    /// Tracked items that contain changes that are not staged.
+    Unstaged,
+    /// Items that are not tracked by Git.
+    Untracked,
+}
+
+impl StatusFilter {
+    /// Get the Git status bits that belong to this category.
+    pub fn status_bits(&self) -> Status {
+        match self {
+            StatusFilter::Conflicted => Status::CONFLICTED,
+            StatusFilter::Deleted => Status::INDEX_DELETED | Status::WT_DELETED,
+            StatusFilter::Modified => Status::INDEX_MODIFIED | Status::WT_MODIFIED,
+            StatusFilter::Staged => {
+                Status::INDEX_NEW
+                    | Status::INDEX_MODIFIED
+                    | Status::INDEX_DELETED
+                    | Status::INDEX_RENAMED
+                    | Status::INDEX_TYPECHANGE
+            }
+            StatusFilter::Unstaged => {
+                Status::WT_MODIFIED
+                    | Status::WT_DELETED
+                    | Status::WT_RENAMED
+                    | Status::WT_TYPECHANGE
+            }
+            StatusFilter::Untracked => Status::WT_NEW,
+        }
+    }
+}
+
+impl FromStr for StatusFilter {
+    type Err = String;
+
+    /// Convert the value passed into `--only` into a `StatusFilter`.
+    fn from_str(status_filter: &str) -> Result<Self, Self::Err> {
+        match status_filter.to_lowercase().as_str() {
+            "conflicted" => Ok(StatusFilter::Conflicted),
+            "deleted" => Ok(StatusFilter::Deleted),
+            "modified" => Ok(StatusFilter::Modified),
+            "staged" => Ok(StatusFilter::Staged),
+            "unstaged" => Ok(StatusFilter::Unstaged),
+            "untracked" => Ok(StatusFilter::Untracked),
+            _ => Err(format!(
+                "{status_filter} is not a supported Git status category"
+            )),
+        }
+    }
+}
+
+/// Check whether the Git status bits belong to any of the categories. Every status,
+/// including no status at all, matches if no categories were passed.
+pub fn matches_status_filters(status_filters: &[StatusFilter], status: Option<Status>) -> bool {
+    status_filters.is_empty()
+        || status.is_some_and(|status| {
+            status_filters
+                .iter()
+                .any(|status_filter| status.intersects(status_filter.status_bits()))
+        })
+}
+
+/// Try to extend the `HashMap` containing Git statuses and their corresponding
 /// filenames with new Git repository items.
-pub fn extend_marker_map(
-    args: &StyleArgs,
-    git_markers: &mut HashMap<String, String>,
-    nomad_style: &NomadStyle,
+pub fn extend_status_map(
+    git_statuses: &mut HashMap<String, Status>,
+    include_ignored: bool,
     target_directory: &str,
 ) {
     if let Some(repo) = get_repo(target_directory) {
-        if let Ok(top_level_map) = get_status_markers(args, nomad_style, &repo, target_directory) {
-            git_markers.extend(top_level_map);
+        if let Ok(top_level_map) = get_statuses(&repo, include_ignored, target_directory) {
+            git_statuses.extend(top_level_map);
         }
     }
 }
 
-/// Get the status markers (colored initials) that correspond with the Git status
-/// of tracked files in the repository.
-pub fn get_status_markers(
-    args: &StyleArgs,
-    nomad_style: &NomadStyle,
+/// Extend the `HashMap` containing Git statuses only if the directory is the root
+/// of a Git repository.
+///
+/// Subdirectories of a repository share the statuses that were already found for it,
+/// so checking every directory while walking would repeatedly compute the same statuses.
+pub fn extend_status_map_at_root(
+    git_statuses: &mut HashMap<String, Status>,
+    include_ignored: bool,
+    directory: &str,
+) {
+    if Path::new(directory).join(".git").exists() {
+        extend_status_map(git_statuses, include_ignored, directory);
+    }
+}
+
+/// Get the Git status of each changed item in the repository, keyed by its absolute path.
+///
+/// Files that are ignored by Git are included as well if `include_ignored` is set.
+pub fn get_statuses(
     repo: &Repository,
+    include_ignored: bool,
     target_directory: &str,
-) -> Result<HashMap<String, String>, NomadError> {
+) -> Result<HashMap<String, Status>, NomadError> {
     let mut status_options = StatusOptions::new();
     status_options
         .show(StatusShow::IndexAndWorkdir)
         .include_untracked(true)
-        .recurse_untracked_dirs(true);
+        .recurse_untracked_dirs(true)
+        .include_ignored(include_ignored)
+        .recurse_ignored_dirs(include_ignored);
 
-    let mut formatted_items = HashMap::new();
+    let mut statuses = HashMap::new();
 
     for repo_item in repo.statuses(Some(&mut status_options))?.iter() {
         let item_name = repo
@@ -49,137 +140,229 @@ pub fn get_status_markers(
             .unwrap_or("?")
             .to_string();
 
-        let items = {
-            let marker = match repo_item.status() {
-                s if s.contains(Status::INDEX_DELETED) => {
-                    if args.no_colors {
-                        nomad_style.git.staged_deleted_marker.clone()
-                    } else {
-                        nomad_style
-                            .git
-                            .staged_deleted_color
-                            .paint(&nomad_style.git.staged_deleted_marker)
-                            .to_string()
-                    }
-                }
-                s if s.contains(Status::INDEX_MODIFIED) => {
-                    if args.no_colors {
-                        nomad_style.git.staged_modified_marker.clone()
-                    } else {
-                        nomad_style
-                            .git
-                            .staged_modified_color
-                            .paint(&nomad_style.git.staged_modified_marker)
-                            .to_string()
-                    }
-                }
-                s if s.contains(Status::INDEX_NEW) => {
-                    if args.no_colors {
-                        nomad_style.git.staged_added_marker.clone()
-                    } else {
-                        nomad_style
-                            .git
-                            .staged_added_color
-                            .paint(&nomad_style.git.staged_added_marker)
-                            .to_string()
-                    }
-                }
-                s if s.contains(Status::INDEX_RENAMED) => {
-                    if args.no_colors {
-                        nomad_style.git.staged_renamed_marker.clone()
-                    } else {
-                        nomad_style
-                            .git
-                            .staged_renamed_color
-                            .paint(&nomad_style.git.staged_renamed_marker)
-                            .to_string()
-                    }
-                }
-                s if s.contains(Status::INDEX_TYPECHANGE) => {
-                    if args.no_colors {
-                        nomad_style.git.staged_typechanged_marker.clone()
-                    } else {
-                        nomad_style
-                            .git
-                            .staged_typechanged_color
-                            .paint(&nomad_style.git.staged_typechanged_marker)
-                            .to_string()
-                    }
-                }
-                s if s.contains(Status::WT_DELETED) => {
-                    if args.no_colors {
-                        nomad_style.git.deleted_marker.clone()
-                    } else {
-                        nomad_style
-                            .git
-                            .deleted_color
-                            .paint(&nomad_style.git.deleted_marker)
-                            .to_string()
-                    }
-                }
-                s if s.contains(Status::WT_MODIFIED) => {
-                    if args.no_colors {
-                        nomad_style.git.modified_marker.clone()
-                    } else {
-                        nomad_style
-                            .git
-                            .modified_color
-                            .paint(&nomad_style.git.modified_marker)
-                            .to_string()
-                    }
-                }
-                s if s.contains(Status::WT_NEW) => {
-                    if args.no_colors {
-                        nomad_style.git.untracked_marker.clone()
-                    } else {
-                        nomad_style
-                            .git
-                            .untracked_color
-                            .paint(&nomad_style.git.untracked_marker)
-                            .to_string()
-                    }
-                }
-                s if s.contains(Status::WT_RENAMED) => {
-                    if args.no_colors {
-                        nomad_style.git.renamed_marker.clone()
-                    } else {
-                        nomad_style
-                            .git
-                            .renamed_color
-                            .paint(&nomad_style.git.renamed_marker)
-                            .to_string()
-                    }
-                }
-                s if s.contains(Status::WT_TYPECHANGE) => {
-                    if args.no_colors {
-                        nomad_style.git.typechanged_marker.clone()
-                    } else {
-                        nomad_style
-                            .git
-                            .typechanged_color
-                            .paint(&nomad_style.git.typechanged_marker)
-                            .to_string()
-                    }
-                }
-                s if s.contains(Status::CONFLICTED) => {
-                    if args.no_colors {
-                        nomad_style.git.conflicted_marker.clone()
-                    } else {
-                        nomad_style
-                            .git
-                            .conflicted_color
-                            .paint(&nomad_style.git.conflicted_marker)
-                            .to_string()
-                    }
-                }
-                _ => "".to_string(),
-            };
-
-            (item_name, marker)
-        };
-
-        formatted_items.insert(items.0, items.1);
+        statuses.insert(item_name, repo_item.status());
     }
 
-    Ok(formatted_items)
+    Ok(statuses)
+}
+
+/// Get the status markers (colored initials) that correspond with the Git status
+/// of tracked files in the repository.
+pub fn get_status_markers(
+    args: &StyleArgs,
+    nomad_style: &NomadStyle,
+    repo: &Repository,
+    target_directory: &str,
+) -> Result<HashMap<String, String>, NomadError> {
+    Ok(get_statuses(repo, false, target_directory)?
+        .into_iter()
+        .map(|(item_name, status)| (item_name, get_status_marker(args, nomad_style, status)))
+        .collect())
+}
+
+/// Reduce the Git status bits of an item to the single status that is displayed
+/// in the tree.
+///
+/// Staged changes take precedence over unstaged changes.
+pub fn get_git_status(status: Status) -> Option<GitStatus> {
+    match status {
+        s if s.contains(Status::INDEX_DELETED) => Some(GitStatus::StagedDeleted),
+        s if s.contains(Status::INDEX_MODIFIED) => Some(GitStatus::StagedModified),
+        s if s.contains(Status::INDEX_NEW) => Some(GitStatus::StagedAdded),
+        s if s.contains(Status::INDEX_RENAMED) => Some(GitStatus::StagedRenamed),
+        s if s.contains(Status::INDEX_TYPECHANGE) => Some(GitStatus::StagedTypechanged),
+        s if s.contains(Status::WT_DELETED) => Some(GitStatus::Deleted),
+        s if s.contains(Status::WT_MODIFIED) => Some(GitStatus::Modified),
+        s if s.contains(Status::WT_NEW) => Some(GitStatus::Untracked),
+        s if s.contains(Status::WT_RENAMED) => Some(GitStatus::Renamed),
+        s if s.contains(Status::WT_TYPECHANGE) => Some(GitStatus::Typechanged),
+        s if s.contains(Status::CONFLICTED) => Some(GitStatus::Conflicted),
+        s if s.contains(Status::IGNORED) => Some(GitStatus::Ignored),
+        _ => None,
+    }
+}
+
+/// Get the status marker (colored initials) that corresponds with the Git status.
+pub fn get_status_marker(args: &StyleArgs, nomad_style: &NomadStyle, status: Status) -> String {
+    match get_git_status(status) {
+        Some(git_status) => {
+            let (color, marker) = get_status_style(nomad_style, git_status);
+
+            if args.no_colors {
+                marker.to_string()
+            } else {
+                color.paint(marker).to_string()
+            }
+        }
+        None => "".to_string(),
+    }
+}
+
+/// Get the color and marker that correspond with the Git status.
+pub fn get_status_style(nomad_style: &NomadStyle, git_status: GitStatus) -> (Style, &str) {
+    match git_status {
+        GitStatus::Conflicted => (
+            nomad_style.git.conflicted_color,
+            &nomad_style.git.conflicted_marker,
+        ),
+        GitStatus::Deleted => (
+            nomad_style.git.deleted_color,
+            &nomad_style.git.deleted_marker,
+        ),
+        GitStatus::Ignored => (
+            nomad_style.git.ignored_color,
+            &nomad_style.git.ignored_marker,
+        ),
+        GitStatus::Modified => (
+            nomad_style.git.modified_color,
+            &nomad_style.git.modified_marker,
+        ),
+        GitStatus::Renamed => (
+            nomad_style.git.renamed_color,
+            &nomad_style.git.renamed_marker,
+        ),
+        GitStatus::StagedAdded => (
+            nomad_style.git.staged_added_color,
+            &nomad_style.git.staged_added_marker,
+        ),
+        GitStatus::StagedDeleted => (
+            nomad_style.git.staged_deleted_color,
+            &nomad_style.git.staged_deleted_marker,
+        ),
+        GitStatus::StagedModified => (
+            nomad_style.git.staged_modified_color,
+            &nomad_style.git.staged_modified_marker,
+        ),
+        GitStatus::StagedRenamed => (
+            nomad_style.git.staged_renamed_color,
+            &nomad_style.git.staged_renamed_marker,
+        ),
+        GitStatus::StagedTypechanged => (
+            nomad_style.git.staged_typechanged_color,
+            &nomad_style.git.staged_typechanged_marker,
+        ),
+        GitStatus::Typechanged => (
+            nomad_style.git.typechanged_color,
+            &nomad_style.git.typechanged_marker,
+        ),
+        GitStatus::Untracked => (
+            nomad_style.git.untracked_color,
+            &nomad_style.git.untracked_marker,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test_markers {
+    use super::*;
+    use crate::cli::global::GlobalArgs;
+
+    use structopt::StructOpt;
+
+    #[test]
+    fn test_status_filter_from_str() {
+        assert_eq!(
+            StatusFilter::from_str("conflicted"),
+            Ok(StatusFilter::Conflicted)
+        );
+        assert_eq!(StatusFilter::from_str("deleted"), Ok(StatusFilter::Deleted));
+        assert_eq!(
+            StatusFilter::from_str("modified"),
+            Ok(StatusFilter::Modified)
+        );
+        assert_eq!(StatusFilter::from_str("staged"), Ok(StatusFilter::Staged));
+        assert_eq!(
+            StatusFilter::from_str("unstaged"),
+            Ok(StatusFilter::Unstaged)
+        );
+        assert_eq!(
+            StatusFilter::from_str("Untracked"),
+            Ok(StatusFilter::Untracked)
+        );
+    }
+
+    #[test]
+    fn test_status_filter_from_str_unknown() {
+        assert_eq!(
+            StatusFilter::from_str("ignored"),
+            Err("ignored is not a supported Git status category".to_string())
+        );
+        assert!(StatusFilter::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_only_combines_values() {
+        let args = GlobalArgs::from_iter(["nd", "--only", "staged,untracked", "--only", "deleted"]);
+        assert_eq!(
+            args.modifiers.only,
+            vec![
+                StatusFilter::Staged,
+                StatusFilter::Untracked,
+                StatusFilter::Deleted
+            ]
+        );
+
+        assert!(GlobalArgs::from_iter_safe(["nd", "--only", "ignored"]).is_err());
+    }
+
+    #[test]
+    fn test_matches_status_filters_categories() {
+        let cases = [
+            (StatusFilter::Conflicted, Status::CONFLICTED),
+            (StatusFilter::Deleted, Status::INDEX_DELETED),
+            (StatusFilter::Deleted, Status::WT_DELETED),
+            (StatusFilter::Modified, Status::INDEX_MODIFIED),
+            (StatusFilter::Modified, Status::WT_MODIFIED),
+            (StatusFilter::Staged, Status::INDEX_NEW),
+            (StatusFilter::Staged, Status::INDEX_RENAMED),
+            (StatusFilter::Unstaged, Status::WT_MODIFIED),
+            (StatusFilter::Unstaged, Status::WT_TYPECHANGE),
+            (StatusFilter::Untracked, Status::WT_NEW),
+        ];
+        for (status_filter, status) in cases {
+            assert!(matches_status_filters(&[status_filter], Some(status)));
+        }
+
+        assert!(!matches_status_filters(
+            &[StatusFilter::Staged],
+            Some(Status::WT_MODIFIED)
+        ));
+        assert!(!matches_status_filters(
+            &[StatusFilter::Unstaged],
+            Some(Status::WT_NEW)
+        ));
+        assert!(!matches_status_filters(
+            &[StatusFilter::Untracked],
+            Some(Status::INDEX_NEW)
+        ));
+        assert!(!matches_status_filters(&[StatusFilter::Modified], None));
+    }
+
+    #[test]
+    fn test_matches_status_filters_combined() {
+        let status_filters = [StatusFilter::Staged, StatusFilter::Untracked];
+
+        assert!(matches_status_filters(
+            &status_filters,
+            Some(Status::INDEX_MODIFIED)
+        ));
+        assert!(matches_status_filters(
+            &status_filters,
+            Some(Status::WT_NEW)
+        ));
+        assert!(matches_status_filters(
+            &status_filters,
+            Some(Status::INDEX_MODIFIED | Status::WT_MODIFIED)
+        ));
+        assert!(!matches_status_filters(
+            &status_filters,
+            Some(Status::WT_DELETED)
+        ));
+    }
+
+    #[test]
+    fn test_matches_status_filters_without_filters() {
+        assert!(matches_status_filters(&[], Some(Status::WT_NEW)));
+        assert!(matches_status_filters(&[], None));
+    }
 }
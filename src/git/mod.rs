@@ -2,9 +2,13 @@
 
 pub mod blame;
 pub mod branch;
+pub mod churn;
 pub mod commit;
 pub mod diff;
+pub mod hunks;
 pub mod markers;
+pub mod owners;
+pub mod revision;
 pub mod status;
 pub mod trees;
 pub mod utils;
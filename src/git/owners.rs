@@ -0,0 +1,253 @@
+//! Display the owners of each item in tree form from Git blame data.
+
+use super::revision::{get_prefix_tree, get_relative_path, get_revision_commit};
+use crate::{
+    cli::{
+        git,
+        global::{GlobalArgs, GraphArgs, LabelArgs, MetaArgs, ModifierArgs, RegexArgs, StyleArgs},
+    },
+    errors::NomadError,
+    models::JsonItem,
+    style::models::NomadStyle,
+    traverse::{
+        models::{FoundItem, ItemOwners, TransformedItem},
+        modes::NomadMode,
+        traits::{ToJson, ToTree, TransformFound},
+        utils::{get_sort_options, sort_items},
+    },
+};
+
+use anyhow::Result;
+use git2::{BlameOptions, ObjectType, Repository, TreeWalkMode, TreeWalkResult};
+use ptree::{item::StringItem, PrintConfig};
+use regex::Regex;
+
+use std::{collections::HashMap, path::Path, str::FromStr, time::UNIX_EPOCH};
+
+/// The values that may identify the owner of each line with `--by`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OwnerKey {
+    /// Lines are owned by the names of their authors.
+    Author,
+    /// Lines are owned by the emails of their authors.
+    Email,
+}
+
+impl FromStr for OwnerKey {
+    type Err = String;
+
+    /// Convert the value passed into `--by` into an `OwnerKey`.
+    fn from_str(owner_key: &str) -> Result<Self, Self::Err> {
+        match owner_key.to_lowercase().as_str() {
+            "author" => Ok(OwnerKey::Author),
+            "email" => Ok(OwnerKey::Email),
+            _ => Err(format!("{owner_key} is not a supported owner key")),
+        }
+    }
+}
+
+/// Build a tree of the files within the target directory at `HEAD`. Each item
+/// displays the authors that own the most lines in it.
+pub fn display_owners_tree(
+    args: &git::OwnersOptions,
+    nomad_style: &NomadStyle,
+    repo: &Repository,
+    target_directory: &str,
+) -> Result<Option<(StringItem, PrintConfig, Option<JsonItem>)>, NomadError> {
+    let global_args = GlobalArgs {
+        export: args.export.clone(),
+        export_format: args.export_format,
+        graph: GraphArgs {
+            graph_depth: args.graph.graph_depth,
+            graph_max_nodes: args.graph.graph_max_nodes,
+        },
+        json: args.json,
+        labels: LabelArgs {
+            all_labels: args.labels.all_labels,
+            label_directories: args.labels.label_directories,
+            numbers: args.labels.numbers,
+        },
+        meta: MetaArgs::default(),
+        modifiers: ModifierArgs::default(),
+        regex: RegexArgs {
+            grep: None,
+            pattern: args.pattern.clone(),
+        },
+        style: StyleArgs {
+            no_colors: args.style.no_colors,
+            no_git: args.style.no_git,
+            no_icons: args.style.no_icons,
+            plain: args.style.plain,
+        },
+        statistics: args.statistics,
+        stream: false,
+    };
+
+    let transformed_items = sort_items(
+        get_owner_items(args, repo, target_directory)?,
+        &get_sort_options(&global_args, nomad_style),
+        target_directory,
+    );
+
+    if global_args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(
+                &transformed_items.to_json(&global_args, target_directory)?
+            )?
+        );
+
+        return Ok(None);
+    }
+
+    let json_item = match (&global_args.export, global_args.export_format) {
+        (Some(_), export_format) if export_format.is_structured() => {
+            Some(transformed_items.to_json(&global_args, target_directory)?)
+        }
+        _ => None,
+    };
+
+    let (tree, config, _) = transformed_items.to_tree(
+        &global_args,
+        NomadMode::Normal,
+        nomad_style,
+        target_directory,
+    )?;
+
+    Ok(Some((tree, config, json_item)))
+}
+
+/// Blame every text file within the target directory at `HEAD` and set the owners of
+/// each item. A directory's owners are the totals of every file within it.
+///
+/// Hidden files are skipped to match the standard tree. If a time was passed into
+/// `--since`, lines that were committed before it are not counted.
+fn get_owner_items(
+    args: &git::OwnersOptions,
+    repo: &Repository,
+    target_directory: &str,
+) -> Result<Vec<TransformedItem>, NomadError> {
+    let commit = get_revision_commit(repo, "HEAD")?;
+    let prefix = get_relative_path(repo, target_directory)?;
+    let tree = get_prefix_tree(&commit, &prefix, repo, "HEAD")?;
+
+    let regex_expression = match args.pattern {
+        Some(ref pattern) => Some(Regex::new(pattern).map_err(NomadError::RegexError)?),
+        None => None,
+    };
+
+    let mut found_items = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() != Some(ObjectType::Blob) {
+            return TreeWalkResult::Ok;
+        }
+
+        let relative_path = format!("{root}{}", entry.name().unwrap_or("?"));
+        if relative_path.split('/').any(|name| name.starts_with('.')) {
+            return TreeWalkResult::Ok;
+        }
+
+        // Binary files do not contain lines to blame.
+        if repo
+            .find_blob(entry.id())
+            .map_or(true, |blob| blob.is_binary())
+        {
+            return TreeWalkResult::Ok;
+        }
+
+        let matched = match regex_expression {
+            Some(ref regex) => match regex.find(&relative_path) {
+                Some(matched) => Some((matched.start(), matched.end())),
+                None => return TreeWalkResult::Ok,
+            },
+            None => None,
+        };
+
+        found_items.push(FoundItem {
+            line_matches: Vec::new(),
+            marker: None,
+            matched,
+            path: Path::new(target_directory)
+                .join(&relative_path)
+                .to_str()
+                .unwrap_or("?")
+                .to_string(),
+            status: None,
+        });
+
+        TreeWalkResult::Ok
+    })?;
+
+    found_items.sort_by(|first, second| Path::new(&first.path).cmp(Path::new(&second.path)));
+
+    let since = args
+        .since
+        .and_then(|since| since.0.duration_since(UNIX_EPOCH).ok())
+        .and_then(|duration| i64::try_from(duration.as_secs()).ok());
+
+    let mut blame_options = BlameOptions::new();
+    blame_options.newest_commit(commit.id());
+
+    let mut item_lines: HashMap<String, HashMap<String, usize>> = HashMap::new();
+    for found_item in found_items.iter() {
+        let relative_path = prefix.join(
+            Path::new(&found_item.path)
+                .strip_prefix(target_directory)
+                .unwrap_or_else(|_| Path::new("?")),
+        );
+        let blame = repo.blame_file(&relative_path, Some(&mut blame_options))?;
+
+        for hunk in blame.iter() {
+            let signature = hunk.final_signature();
+            if since.is_some_and(|since| signature.when().seconds() < since) {
+                continue;
+            }
+
+            let owner = match args.by {
+                OwnerKey::Author => signature.name().unwrap_or("Unknown author"),
+                OwnerKey::Email => signature.email().unwrap_or("Unknown email"),
+            };
+
+            for ancestor in Path::new(&found_item.path).ancestors() {
+                if ancestor == Path::new(target_directory) {
+                    break;
+                }
+
+                *item_lines
+                    .entry(ancestor.to_str().unwrap_or("?").to_string())
+                    .or_default()
+                    .entry(owner.to_string())
+                    .or_insert(0) += hunk.lines_in_hunk();
+            }
+        }
+    }
+
+    let mut transformed_items = found_items.transform(target_directory)?;
+    for item in transformed_items.iter_mut() {
+        item.owners = item_lines
+            .get(&item.path)
+            .map(|owner_lines| get_top_owners(owner_lines, args.top));
+    }
+
+    Ok(transformed_items)
+}
+
+/// Get the owners with the most lines, most lines first. Owners with the same number
+/// of lines are sorted by name so that the order does not change between runs.
+fn get_top_owners(owner_lines: &HashMap<String, usize>, top: usize) -> ItemOwners {
+    let mut owners = owner_lines
+        .iter()
+        .map(|(owner, lines)| (owner.to_string(), *lines))
+        .collect::<Vec<(String, usize)>>();
+    owners.sort_by(|(first_owner, first_lines), (second_owner, second_lines)| {
+        second_lines
+            .cmp(first_lines)
+            .then_with(|| first_owner.cmp(second_owner))
+    });
+    owners.truncate(top);
+
+    ItemOwners {
+        owners,
+        total_lines: owner_lines.values().sum(),
+    }
+}
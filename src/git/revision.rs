@@ -0,0 +1,516 @@
+//! Display the tree of a Git revision without checking it out.
+
+use super::utils::{get_repo, get_repo_root};
+use crate::{
+    cli::global::GlobalArgs,
+    errors::NomadError,
+    models::JsonItem,
+    style::models::NomadStyle,
+    traverse::{
+        models::{FoundItem, ItemSize, LastCommit, RevisionMetadata, TransformedItem},
+        modes::NomadMode,
+        traits::{ToJson, ToTree, TransformFound},
+        utils::{get_sort_options, sort_items},
+    },
+};
+
+use anyhow::{anyhow, Result};
+use git2::{
+    Commit, DiffOptions, ObjectType, Oid, Repository, Sort, Tree, TreeWalkMode, TreeWalkResult,
+};
+use ptree::{item::StringItem, PrintConfig};
+use regex::Regex;
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+/// Build the tree of the revision passed into `--rev`.
+///
+/// A JSON tree is also returned if the tree is exported to a format that is built
+/// from it.
+pub fn walk_revision(
+    args: &GlobalArgs,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+) -> Result<(StringItem, PrintConfig, Option<JsonItem>), NomadError> {
+    let transformed_items = get_revision_items(args, nomad_style, target_directory)?;
+
+    let json_item = match (&args.export, args.export_format) {
+        (Some(_), export_format) if export_format.is_structured() => {
+            Some(transformed_items.to_json(args, target_directory)?)
+        }
+        _ => None,
+    };
+
+    let (tree, config, _) =
+        transformed_items.to_tree(args, NomadMode::Normal, nomad_style, target_directory)?;
+
+    Ok((tree, config, json_item))
+}
+
+/// Display the tree of the revision passed into `--rev` as a JSON document.
+pub fn json_revision(
+    args: &GlobalArgs,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+) -> Result<(), NomadError> {
+    let json_item =
+        get_revision_items(args, nomad_style, target_directory)?.to_json(args, target_directory)?;
+
+    println!("{}", serde_json::to_string_pretty(&json_item)?);
+
+    Ok(())
+}
+
+/// Resolve the revision passed into `--rev` to the full SHA of its commit.
+pub fn resolve_revision(revision: &str, target_directory: &str) -> Result<String, NomadError> {
+    let repo = get_repo(target_directory)
+        .ok_or_else(|| NomadError::Error(anyhow!("Cannot browse Git revisions here!")))?;
+
+    let commit_id = get_revision_commit(&repo, revision)?.id().to_string();
+
+    Ok(commit_id)
+}
+
+/// Get the contents of a file at the commit with the given SHA.
+pub fn get_blob_contents(path: &str, revision: &str) -> Result<Vec<u8>, NomadError> {
+    let directory = Path::new(path)
+        .ancestors()
+        .find(|ancestor| ancestor.is_dir())
+        .and_then(|ancestor| ancestor.to_str())
+        .unwrap_or(".");
+    let repo = get_repo(directory)
+        .ok_or_else(|| NomadError::Error(anyhow!("Cannot browse Git revisions here!")))?;
+
+    let relative_path = get_relative_path(&repo, path)?;
+    let blob = repo
+        .revparse_single(&format!(
+            "{revision}:{}",
+            relative_path.to_str().unwrap_or("?")
+        ))?
+        .peel_to_blob()?;
+
+    Ok(blob.content().to_vec())
+}
+
+/// Find the commit that last changed each path in `paths`, starting from `commit`.
+///
+/// Paths are relative to the root of the repository. Like `git log -1 -- <path>`, a
+/// merge is only credited with a path if the path differs from every parent of the
+/// merge. Otherwise the path is followed through the first parent it is identical
+/// to, so changes are credited to the commits that made them. The history is walked
+/// once, newest commit first, and the walk stops early once every path has been
+/// assigned a commit.
+pub fn get_last_commits(
+    commit: &Commit,
+    paths: &HashSet<String>,
+    repo: &Repository,
+) -> Result<HashMap<String, LastCommit>, NomadError> {
+    let mut last_commits: HashMap<String, LastCommit> = HashMap::new();
+
+    // The paths that are followed through each commit that has not been visited yet.
+    let mut followed_paths: HashMap<Oid, HashSet<String>> = HashMap::new();
+    followed_paths.insert(commit.id(), paths.clone());
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(commit.id())?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+
+    for oid in revwalk {
+        if followed_paths.is_empty() {
+            break;
+        }
+
+        let oid = oid?;
+        let commit_paths = match followed_paths.remove(&oid) {
+            Some(commit_paths) => commit_paths,
+            None => continue,
+        };
+
+        let commit = repo.find_commit(oid)?;
+        let commit_tree = commit.tree()?;
+
+        // The root commit is compared against an empty tree.
+        let mut changed_paths = Vec::new();
+        if commit.parent_count() == 0 {
+            changed_paths.push(get_changed_paths(None, &commit_tree, &commit_paths, repo)?);
+        }
+        for parent in commit.parents() {
+            changed_paths.push(get_changed_paths(
+                Some(&parent.tree()?),
+                &commit_tree,
+                &commit_paths,
+                repo,
+            )?);
+        }
+
+        let parent_ids = commit.parent_ids().collect::<Vec<Oid>>();
+        let mut last_commit = None;
+        for path in commit_paths {
+            match changed_paths
+                .iter()
+                .position(|changed| !changed.contains(&path))
+            {
+                Some(index) if index < parent_ids.len() => {
+                    followed_paths
+                        .entry(parent_ids[index])
+                        .or_default()
+                        .insert(path);
+                }
+                _ => {
+                    let last_commit = last_commit
+                        .get_or_insert_with(|| get_commit_details(&commit))
+                        .clone();
+                    last_commits.insert(path, last_commit);
+                }
+            }
+        }
+    }
+
+    Ok(last_commits)
+}
+
+/// Get the paths in `paths` that differ between the two trees. A directory differs
+/// if anything within it differs.
+fn get_changed_paths(
+    old_tree: Option<&Tree>,
+    new_tree: &Tree,
+    paths: &HashSet<String>,
+    repo: &Repository,
+) -> Result<HashSet<String>, NomadError> {
+    let mut diff_options = DiffOptions::new();
+    diff_options.ignore_submodules(true);
+
+    let diff = repo.diff_tree_to_tree(old_tree, Some(new_tree), Some(&mut diff_options))?;
+
+    let mut changed_paths = HashSet::new();
+    for delta in diff.deltas() {
+        if let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) {
+            for ancestor in path.ancestors() {
+                let key = ancestor.to_str().unwrap_or("?");
+                if paths.contains(key) {
+                    changed_paths.insert(key.to_string());
+                }
+            }
+        }
+    }
+
+    Ok(changed_paths)
+}
+
+/// Set the commit that last changed each item in the working tree, starting from
+/// `HEAD`.
+///
+/// Only paths that exist at `HEAD` are looked up, so the history walk is not
+/// prolonged by untracked files, which are left without a commit.
+pub fn set_last_commits(
+    items: &mut [TransformedItem],
+    target_directory: &str,
+) -> Result<(), NomadError> {
+    let repo = get_repo(target_directory)
+        .ok_or_else(|| NomadError::Error(anyhow!("Cannot display last commits here!")))?;
+
+    // There is no history to walk before the first commit.
+    let commit = match repo.head().and_then(|head| head.peel_to_commit()) {
+        Ok(commit) => commit,
+        Err(_) => return Ok(()),
+    };
+    let tree = commit.tree()?;
+    let repo_root = get_repo_root(&repo)?;
+
+    let relative_paths = items
+        .iter()
+        .map(|item| {
+            Path::new(&item.path)
+                .strip_prefix(&repo_root)
+                .ok()
+                .and_then(|relative_path| relative_path.to_str())
+                .map(|relative_path| relative_path.to_string())
+        })
+        .collect::<Vec<Option<String>>>();
+    let paths = relative_paths
+        .iter()
+        .flatten()
+        .filter(|relative_path| tree.get_path(Path::new(relative_path)).is_ok())
+        .cloned()
+        .collect::<HashSet<String>>();
+
+    let last_commits = get_last_commits(&commit, &paths, &repo)?;
+    for (item, relative_path) in items.iter_mut().zip(relative_paths) {
+        item.last_commit = relative_path
+            .and_then(|relative_path| last_commits.get(&relative_path))
+            .cloned();
+    }
+
+    Ok(())
+}
+
+/// Resolve a revision such as a branch, a tag, `HEAD~5`, or a SHA to a commit.
+pub fn get_revision_commit<'a>(
+    repo: &'a Repository,
+    revision: &str,
+) -> Result<Commit<'a>, NomadError> {
+    repo.revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|error| NomadError::GitError {
+            context: format!("Unable to resolve the revision {revision}"),
+            source: error,
+        })
+}
+
+/// Get the tree of the directory at `prefix` within the commit. `prefix` is relative
+/// to the root of the repository, and the commit's root tree is returned if it is
+/// empty.
+pub fn get_prefix_tree<'a>(
+    commit: &Commit<'a>,
+    prefix: &Path,
+    repo: &'a Repository,
+    revision: &str,
+) -> Result<Tree<'a>, NomadError> {
+    if prefix.as_os_str().is_empty() {
+        return Ok(commit.tree()?);
+    }
+
+    commit
+        .tree()?
+        .get_path(prefix)
+        .and_then(|entry| entry.to_object(repo))
+        .and_then(|object| object.peel_to_tree())
+        .map_err(|error| NomadError::GitError {
+            context: format!("{} does not exist at {revision}", prefix.display()),
+            source: error,
+        })
+}
+
+/// Get the path relative to the root of the repository.
+pub fn get_relative_path(repo: &Repository, path: &str) -> Result<PathBuf, NomadError> {
+    let repo_root = get_repo_root(repo)?;
+
+    Path::new(path)
+        .strip_prefix(&repo_root)
+        .map(|relative_path| relative_path.to_path_buf())
+        .map_err(|_| {
+            NomadError::PathError(format!(
+                "{path} is not within the Git repository at {}",
+                repo_root.display()
+            ))
+        })
+}
+
+/// Get the short SHA, author, time, and summary of a commit.
+fn get_commit_details(commit: &Commit) -> LastCommit {
+    LastCommit {
+        author: commit.author().name().unwrap_or("?").to_string(),
+        id: commit
+            .as_object()
+            .short_id()
+            .ok()
+            .and_then(|short_id| short_id.as_str().map(|id| id.to_string()))
+            .unwrap_or_else(|| commit.id().to_string()),
+        summary: commit.summary().unwrap_or("").to_string(),
+        time: commit.time().seconds(),
+    }
+}
+
+/// Get the files within the target directory at the revision and transform them for
+/// tree building.
+///
+/// Blob sizes are set for every item. The commit that last changed each item is only
+/// looked up if metadata or last commits were requested, since that requires walking
+/// the history.
+fn get_revision_items(
+    args: &GlobalArgs,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+) -> Result<Vec<TransformedItem>, NomadError> {
+    let revision = args.modifiers.rev.as_deref().unwrap_or("HEAD");
+
+    let repo = get_repo(target_directory)
+        .ok_or_else(|| NomadError::Error(anyhow!("Cannot browse Git revisions here!")))?;
+    let commit = get_revision_commit(&repo, revision)?;
+
+    let prefix = get_relative_path(&repo, target_directory)?;
+    let tree = get_prefix_tree(&commit, &prefix, &repo, revision)?;
+
+    let regex_expression = match args.regex.pattern {
+        Some(ref pattern) => Some(Regex::new(pattern).map_err(NomadError::RegexError)?),
+        None => None,
+    };
+
+    let odb = repo.odb()?;
+    let mut blob_sizes: HashMap<String, u64> = HashMap::new();
+    let mut found_items = Vec::new();
+
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() != Some(ObjectType::Blob) {
+            return TreeWalkResult::Ok;
+        }
+
+        let relative_path = format!("{root}{}", entry.name().unwrap_or("?"));
+        let components = relative_path.split('/').collect::<Vec<&str>>();
+
+        if !args.modifiers.hidden && components.iter().any(|name| name.starts_with('.')) {
+            return TreeWalkResult::Ok;
+        }
+        if let Some(max_depth) = args.modifiers.max_depth {
+            if components.len() > max_depth {
+                return TreeWalkResult::Ok;
+            }
+        }
+
+        let matched = match regex_expression {
+            Some(ref regex) => match regex.find(&relative_path) {
+                Some(matched) => Some((matched.start(), matched.end())),
+                None => return TreeWalkResult::Ok,
+            },
+            None => None,
+        };
+
+        let size = odb
+            .read_header(entry.id())
+            .map_or(0, |(size, _)| size as u64);
+        let path = Path::new(target_directory)
+            .join(&relative_path)
+            .to_str()
+            .unwrap_or("?")
+            .to_string();
+
+        blob_sizes.insert(path.clone(), size);
+        found_items.push(FoundItem {
+            line_matches: Vec::new(),
+            marker: None,
+            matched,
+            path,
+            status: None,
+        });
+
+        TreeWalkResult::Ok
+    })?;
+
+    found_items.sort_by(|first, second| Path::new(&first.path).cmp(Path::new(&second.path)));
+
+    let mut transformed_items = found_items.transform(target_directory)?;
+
+    let mut item_sizes: HashMap<String, u64> = HashMap::new();
+    for (path, size) in blob_sizes.iter() {
+        for ancestor in Path::new(path).ancestors() {
+            if ancestor == Path::new(target_directory) {
+                break;
+            }
+
+            *item_sizes
+                .entry(ancestor.to_str().unwrap_or("?").to_string())
+                .or_insert(0) += size;
+        }
+    }
+
+    let last_commits = if args.meta.metadata || args.meta.last_commit {
+        let paths = transformed_items
+            .iter()
+            .map(|item| prefix.join(item.components.join("/")))
+            .map(|path| path.to_str().unwrap_or("?").to_string())
+            .collect::<HashSet<String>>();
+
+        get_last_commits(&commit, &paths, &repo)?
+    } else {
+        HashMap::new()
+    };
+
+    for item in transformed_items.iter_mut() {
+        let size = item_sizes.get(&item.path).copied().unwrap_or(0);
+        let last_commit = prefix
+            .join(item.components.join("/"))
+            .to_str()
+            .and_then(|path| last_commits.get(path))
+            .cloned();
+
+        item.size = Some(ItemSize {
+            apparent: size,
+            disk: size,
+        });
+        if args.meta.last_commit {
+            item.last_commit.clone_from(&last_commit);
+        }
+        item.revision_metadata = Some(RevisionMetadata { last_commit, size });
+    }
+
+    Ok(sort_items(
+        transformed_items,
+        &get_sort_options(args, nomad_style),
+        target_directory,
+    ))
+}
+
+#[cfg(test)]
+mod test_revision {
+    use super::*;
+    use crate::git::utils::test_utils::{commit_files, init_repo};
+
+    #[test]
+    fn test_get_last_commits_through_merge() {
+        let (_directory, repo) = init_repo();
+
+        let root = commit_files(
+            &repo,
+            &[
+                ("a.txt", "1\n"),
+                ("b.txt", "1\n"),
+                ("c.txt", "1\n"),
+                ("dir/d.txt", "1\n"),
+            ],
+            "Add the files",
+            &[],
+        );
+        let side = commit_files(
+            &repo,
+            &[("a.txt", "2\n"), ("c.txt", "2\n")],
+            "Change a.txt and c.txt on side",
+            &[root],
+        );
+        let main = commit_files(
+            &repo,
+            &[("b.txt", "2\n"), ("c.txt", "3\n")],
+            "Change b.txt and c.txt on main",
+            &[root],
+        );
+        // The merge brings in a.txt from side and resolves the conflict in c.txt.
+        let merge = commit_files(
+            &repo,
+            &[("a.txt", "2\n"), ("c.txt", "4\n")],
+            "Merge side",
+            &[main, side],
+        );
+
+        let paths = ["a.txt", "b.txt", "c.txt", "dir", "dir/d.txt"]
+            .into_iter()
+            .map(|path| path.to_string())
+            .collect::<HashSet<String>>();
+        let mut summaries = get_last_commits(&repo.find_commit(merge).unwrap(), &paths, &repo)
+            .unwrap()
+            .into_iter()
+            .map(|(path, last_commit)| (path, last_commit.summary))
+            .collect::<Vec<(String, String)>>();
+        summaries.sort();
+
+        // The file brought in by the merge is credited to the commit that changed it.
+        // Only the conflict resolution differs from both parents.
+        assert_eq!(
+            summaries,
+            vec![
+                (
+                    "a.txt".to_string(),
+                    "Change a.txt and c.txt on side".to_string()
+                ),
+                (
+                    "b.txt".to_string(),
+                    "Change b.txt and c.txt on main".to_string()
+                ),
+                ("c.txt".to_string(), "Merge side".to_string()),
+                ("dir".to_string(), "Add the files".to_string()),
+                ("dir/d.txt".to_string(), "Add the files".to_string()),
+            ]
+        );
+    }
+}
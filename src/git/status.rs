@@ -1,23 +1,29 @@
 //! Display the Git status command in tree form.
 
-use super::markers::get_status_markers;
+use super::{
+    diff::get_numstat,
+    markers::{get_status_marker, get_statuses, matches_status_filters},
+    utils::get_ahead_behind,
+};
 use crate::{
     cli::{
         git,
-        global::{GlobalArgs, LabelArgs, MetaArgs, ModifierArgs, RegexArgs, StyleArgs},
+        global::{GlobalArgs, GraphArgs, LabelArgs, MetaArgs, ModifierArgs, RegexArgs, StyleArgs},
     },
     errors::NomadError,
+    models::JsonItem,
     style::models::NomadStyle,
     traverse::{
-        models::FoundItem,
+        models::{FoundItem, LineChanges},
         modes::NomadMode,
-        traits::{ToTree, TransformFound},
+        traits::{ToJson, ToTree, TransformFound},
+        utils::{get_sort_options, search_file_contents, set_line_changes, sort_items},
     },
 };
 
 use ansi_term::{Colour, Style};
 use anyhow::{Result, __private};
-use git2::{ObjectType, Repository};
+use git2::{BranchType, Repository, Status};
 use itertools::Itertools;
 use ptree::{item::StringItem, PrintConfig};
 use regex::Regex;
@@ -30,10 +36,16 @@ pub fn display_status_tree(
     nomad_style: &NomadStyle,
     repo: &Repository,
     target_directory: &str,
-) -> Result<Option<(StringItem, PrintConfig)>, NomadError> {
+) -> Result<Option<(StringItem, PrintConfig, Option<JsonItem>)>, NomadError> {
     // Hm... There is probably a better solution, but fuck it. Leaving it for now.
     let global_args = GlobalArgs {
         export: args.export.clone(),
+        export_format: args.export_format,
+        graph: GraphArgs {
+            graph_depth: args.graph.graph_depth,
+            graph_max_nodes: args.graph.graph_max_nodes,
+        },
+        json: args.json,
         labels: LabelArgs {
             all_labels: args.labels.all_labels,
             label_directories: args.labels.label_directories,
@@ -42,15 +54,15 @@ pub fn display_status_tree(
         meta: MetaArgs {
             metadata: args.meta.metadata,
             tokei: args.meta.tokei,
+            ..Default::default()
         },
         modifiers: ModifierArgs {
-            dirs: false,
-            disrespect: false,
-            hidden: false,
-            max_depth: None,
-            max_filesize: None,
+            only: args.only.clone(),
+            show_ignored: args.show_ignored,
+            ..Default::default()
         },
         regex: RegexArgs {
+            grep: args.regex.grep.clone(),
             pattern: args.regex.pattern.clone(),
         },
         style: StyleArgs {
@@ -60,75 +72,157 @@ pub fn display_status_tree(
             plain: args.style.plain,
         },
         statistics: args.statistics,
+        stream: false,
     };
 
-    get_status_markers(&args.style, nomad_style, repo, target_directory).map_or_else(
-        Err,
-        |marker_map| {
-            if marker_map.is_empty() {
-                println!(
-                    "\n{}\n",
-                    Colour::Green
-                        .bold()
-                        .paint("Nothing to commit. Working tree clean.".to_string())
-                );
-
-                Ok(None)
+    get_statuses(repo, args.show_ignored, target_directory).map_or_else(Err, |status_map| {
+        if global_args.json {
+            let found_items =
+                get_status_items(&global_args, status_map, nomad_style, target_directory)?;
+            let transformed_items = if found_items.is_empty() {
+                Vec::new()
             } else {
-                Ok(Some(build_status_tree(
-                    &global_args,
-                    marker_map,
-                    nomad_style,
+                sort_items(
+                    found_items.transform(target_directory)?,
+                    &get_sort_options(&global_args, nomad_style),
                     target_directory,
-                )?))
-            }
-        },
-    )
-}
-
-/// Get the number of commits ahead of `origin`.
-pub fn display_commits_ahead(branch_name: &str, repo: &Repository) -> Result<(), NomadError> {
-    let head_oid = repo.head()?.peel(ObjectType::Commit)?.id();
-
-    let origin_branch = format!("origin/{branch_name}");
-
-    if let Ok(git_object) = repo.revparse_single(&origin_branch) {
-        let last_commit_oid = git_object.id();
+                )
+            };
 
-        let (ahead, _behind) = repo.graph_ahead_behind(head_oid, last_commit_oid)?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(
+                    &transformed_items.to_json(&global_args, target_directory)?
+                )?
+            );
 
-        if ahead > 0 {
+            Ok(None)
+        } else if status_map.is_empty() {
             println!(
-                "{} of {} by {} commit{plurality}.\n  └── Run `{}` to publish your local changes.",
-                Style::new().underline().paint("Ahead"),
-                Colour::Blue.bold().paint(origin_branch),
-                Colour::Green.bold().paint(format!("{}", ahead)),
-                Style::new().bold().paint("git push"),
-                plurality = if ahead > 1 { "s" } else { "" }
+                "\n{}\n",
+                Colour::Green
+                    .bold()
+                    .paint("Nothing to commit. Working tree clean.".to_string())
             );
+
+            Ok(None)
         } else {
+            // Staged and unstaged changes are both counted, so the diffs are only
+            // computed when the line counts are displayed.
+            let numstat = if args.numstat {
+                Some(get_numstat(repo)?)
+            } else {
+                None
+            };
+
+            Ok(Some(build_status_tree(
+                &global_args,
+                status_map,
+                nomad_style,
+                numstat.as_ref(),
+                target_directory,
+            )?))
+        }
+    })
+}
+
+/// Display the number of commits the branch is ahead of and behind the upstream
+/// branch it tracks.
+pub fn display_commits_ahead(branch_name: &str, repo: &Repository) -> Result<(), NomadError> {
+    let upstream = repo
+        .find_branch(branch_name, BranchType::Local)
+        .ok()
+        .and_then(|branch| {
+            let upstream_name = branch.upstream().ok()?.name().ok()??.to_string();
+
+            Some((branch, upstream_name))
+        });
+
+    let (branch, upstream_name) = match upstream {
+        Some(upstream) => upstream,
+        None => {
             println!(
-                "Up to date with {}.",
-                Colour::Blue.bold().paint(origin_branch)
+                "{}",
+                Colour::Fixed(172).bold().paint("No upstream branch found.")
             );
+
+            return Ok(());
         }
-    } else {
-        println!(
-            "{}",
-            Colour::Fixed(172).bold().paint("No upstream branch found.")
-        );
+    };
+    let upstream_branch = Colour::Blue.bold().paint(upstream_name);
+
+    match get_ahead_behind(&branch, repo)? {
+        Some((0, 0)) | None => println!("Up to date with {upstream_branch}."),
+        Some((ahead, 0)) => println!(
+            "{} of {upstream_branch} by {} commit{plurality}.\n  └── Run `{}` to publish your local changes.",
+            Style::new().underline().paint("Ahead"),
+            Colour::Green.bold().paint(format!("{ahead}")),
+            Style::new().bold().paint("git push"),
+            plurality = if ahead > 1 { "s" } else { "" }
+        ),
+        Some((0, behind)) => println!(
+            "{} {upstream_branch} by {} commit{plurality}.\n  └── Run `{}` to update your local branch.",
+            Style::new().underline().paint("Behind"),
+            Colour::Red.bold().paint(format!("{behind}")),
+            Style::new().bold().paint("git pull"),
+            plurality = if behind > 1 { "s" } else { "" }
+        ),
+        Some((ahead, behind)) => println!(
+            "{} from {upstream_branch} by {} and {} commits.\n  └── Run `{}` to merge the upstream changes.",
+            Style::new().underline().paint("Diverged"),
+            Colour::Green.bold().paint(format!("↑{ahead}")),
+            Colour::Red.bold().paint(format!("↓{behind}")),
+            Style::new().bold().paint("git pull")
+        ),
     }
 
     Ok(())
 }
 
-/// Traverse the repo and build the status tree.
+/// Traverse the repo and build the status tree. Each item is annotated with its line
+/// counts if `--numstat` was passed.
 fn build_status_tree(
     args: &GlobalArgs,
-    marker_map: HashMap<String, String>,
+    status_map: HashMap<String, Status>,
+    nomad_style: &NomadStyle,
+    numstat: Option<&HashMap<String, LineChanges>>,
+    target_directory: &str,
+) -> Result<(StringItem, PrintConfig, Option<JsonItem>), NomadError> {
+    let mut transformed_items = get_status_items(args, status_map, nomad_style, target_directory)?
+        .transform(target_directory)?;
+    if let Some(numstat) = numstat {
+        set_line_changes(&mut transformed_items, numstat, target_directory);
+    }
+
+    let transformed_items = sort_items(
+        transformed_items,
+        &get_sort_options(args, nomad_style),
+        target_directory,
+    );
+
+    let json_item = match (&args.export, args.export_format) {
+        (Some(_), export_format) if export_format.is_structured() => {
+            Some(transformed_items.to_json(args, target_directory)?)
+        }
+        _ => None,
+    };
+
+    let (tree, config, _) =
+        transformed_items.to_tree(args, NomadMode::GitStatus, nomad_style, target_directory)?;
+
+    Ok((tree, config, json_item))
+}
+
+/// Get the changed items within the target directory that match the pattern, if
+/// one was provided. If a content search was requested, only files containing a
+/// match are kept, and if Git status categories were passed into `--only`, only
+/// files in those categories are kept.
+fn get_status_items(
+    args: &GlobalArgs,
+    status_map: HashMap<String, Status>,
     nomad_style: &NomadStyle,
     target_directory: &str,
-) -> Result<(StringItem, PrintConfig), NomadError> {
+) -> Result<Vec<FoundItem>, NomadError> {
     let regex_expression = if let Some(ref pattern) = args.regex.pattern {
         match Regex::new(&pattern.clone()) {
             Ok(regex) => Some(regex),
@@ -137,11 +231,32 @@ fn build_status_tree(
     } else {
         None
     };
+    let grep_expression = if let Some(ref grep) = args.regex.grep {
+        match Regex::new(grep) {
+            Ok(regex) => Some(regex),
+            Err(error) => return __private::Err(NomadError::RegexError(error)),
+        }
+    } else {
+        None
+    };
 
-    let (tree, config, _) = marker_map
+    Ok(status_map
         .iter()
-        .filter_map(|(absolute_path, marker)| {
-            if absolute_path.contains(target_directory) {
+        .filter_map(|(absolute_path, status)| {
+            if absolute_path.contains(target_directory)
+                && matches_status_filters(&args.modifiers.only, Some(*status))
+            {
+                let line_matches = match grep_expression {
+                    Some(ref regex) => {
+                        match search_file_contents(Path::new(absolute_path), regex) {
+                            Some(line_matches) => line_matches,
+                            None => return None,
+                        }
+                    }
+                    None => Vec::new(),
+                };
+                let marker = Some(get_status_marker(&args.style, nomad_style, *status));
+
                 match regex_expression {
                     Some(ref regex) => match regex.find(
                         Path::new(&absolute_path)
@@ -151,16 +266,20 @@ fn build_status_tree(
                             .unwrap_or("?"),
                     ) {
                         Some(matched) => Some(FoundItem {
-                            marker: Some(marker.to_string()),
+                            line_matches,
+                            marker,
                             matched: Some((matched.start(), matched.end())),
                             path: absolute_path.clone(),
+                            status: Some(*status),
                         }),
                         None => None,
                     },
                     None => Some(FoundItem {
-                        marker: Some(marker.to_string()),
+                        line_matches,
+                        marker,
                         matched: None,
                         path: absolute_path.to_string(),
+                        status: Some(*status),
                     }),
                 }
             } else {
@@ -168,9 +287,5 @@ fn build_status_tree(
             }
         })
         .sorted_by_key(|found_item| found_item.path.to_string())
-        .collect::<Vec<FoundItem>>()
-        .transform(target_directory)?
-        .to_tree(args, NomadMode::GitStatus, nomad_style, target_directory)?;
-
-    Ok((tree, config))
+        .collect::<Vec<FoundItem>>())
 }
@@ -1,10 +1,11 @@
 //! Modify the Git trees - stages or restores files.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use ansi_term::Colour;
 use git2::{build::CheckoutBuilder, Error, Index, IndexAddOption, Repository, Tree};
 
+use super::utils::get_repo_root;
 use crate::{
     cli::Args,
     style::models::NomadStyle,
@@ -18,8 +19,14 @@ pub enum TreeMode {
     /// Stage all modified, deleted, or untracked files from the working directory
     /// into the index.
     StageAll,
-    /// Restore files in the working directory back to their clean Git state.
-    RestoreWorkingDirectory,
+    /// Restore the index entries of files to their state at `HEAD` or at the revision,
+    /// if one was provided (unstage files). Files that do not exist at the revision
+    /// are removed from the index.
+    RestoreIndex(Option<String>),
+    /// Restore files in the working directory back to their clean Git state. If a
+    /// revision was provided, files are restored to their state at that revision
+    /// instead and the index is not modified.
+    RestoreWorkingDirectory(Option<String>),
 }
 
 /// Modify the Git trees to stage/unstage/restore files.
@@ -28,6 +35,7 @@ pub enum TreeMode {
 ///     * Adds new or modified files to the current index.
 ///     * Restores staged files from the staging area to the index (unstage a file).
 ///     * Restores modified files from the working directory to its clean state.
+///     * Restores files in the working directory or index from another revision.
 ///
 pub fn modify_trees(
     args: &Args,
@@ -37,7 +45,24 @@ pub fn modify_trees(
     stage_mode: TreeMode,
     target_directory: &str,
 ) -> Result<(), Error> {
-    let head_tree = repo.head()?.peel_to_tree()?;
+    // `HEAD` does not point to a commit before the first commit, in which case every
+    // file is new.
+    let source_commit = match stage_mode {
+        TreeMode::RestoreIndex(Some(ref revision))
+        | TreeMode::RestoreWorkingDirectory(Some(ref revision)) => {
+            Some(repo.revparse_single(revision)?.peel_to_commit()?)
+        }
+        TreeMode::RestoreIndex(None) | TreeMode::RestoreWorkingDirectory(None) => {
+            repo.head().and_then(|head| head.peel_to_commit()).ok()
+        }
+        _ => None,
+    };
+    let source_tree = match source_commit {
+        Some(ref commit) => Some(commit.tree()?),
+        None => None,
+    };
+
+    let repo_root = get_repo_root(repo).map_err(|error| Error::from_str(&error.to_string()))?;
     let mut index = repo.index()?;
 
     let mut staged_files = 0;
@@ -58,10 +83,11 @@ pub fn modify_trees(
                 target_directory,
             );
 
+            let mut unstaged_paths: Vec<PathBuf> = Vec::new();
             if let Some(found_items) = found_items {
                 for item in found_items {
                     let target_file = Path::new(&item);
-                    let relative_path = match Path::new(&item).strip_prefix(target_directory) {
+                    let relative_path = match Path::new(&item).strip_prefix(&repo_root) {
                         Ok(prefix_stripped) => prefix_stripped,
                         Err(_) => target_file,
                     };
@@ -74,26 +100,57 @@ pub fn modify_trees(
 
                             staged_files += 1;
                         }
-                        TreeMode::RestoreWorkingDirectory => {
+                        TreeMode::RestoreIndex(_) => {
+                            unstaged_paths.push(relative_path.to_path_buf());
+                            staged_files += 1;
+                        }
+                        TreeMode::RestoreWorkingDirectory(None) => {
                             restore_file(
-                                &head_tree,
+                                source_tree.as_ref(),
                                 &mut index,
                                 relative_path,
                                 repo,
                                 &mut staged_files,
                             )?;
                         }
+                        TreeMode::RestoreWorkingDirectory(Some(ref revision)) => {
+                            match source_tree {
+                                Some(ref source_tree)
+                                    if source_tree.get_path(relative_path).is_ok() =>
+                                {
+                                    restore_file_from_source(source_tree, relative_path, repo)?;
+                                    staged_files += 1;
+                                }
+                                _ => println!(
+                                    "{}",
+                                    Colour::Fixed(172).bold().paint(format!(
+                                        "{} does not exist at {revision}. Skipping.",
+                                        relative_path.display()
+                                    ))
+                                ),
+                            }
+                        }
                         _ => {}
                     }
                 }
             }
 
+            // Entries are reset all at once. Files without an entry in the source tree
+            // are removed from the index.
+            if !unstaged_paths.is_empty() {
+                repo.reset_default(
+                    source_commit.as_ref().map(|commit| commit.as_object()),
+                    unstaged_paths.iter(),
+                )?;
+            }
+
             if staged_files > 0 {
                 index.write()?;
 
                 let info = match stage_mode {
                     TreeMode::Stage => "Staged",
-                    TreeMode::RestoreWorkingDirectory => "Restored",
+                    TreeMode::RestoreIndex(_) => "Unstaged",
+                    TreeMode::RestoreWorkingDirectory(_) => "Restored",
                     _ => "",
                 };
 
@@ -104,7 +161,13 @@ pub fn modify_trees(
                     if staged_files == 1 { "item" } else { "items" }
                 );
             } else {
-                println!("{}\n", Colour::Red.bold().paint("No items were staged!"));
+                let info = match stage_mode {
+                    TreeMode::RestoreIndex(_) => "No items were unstaged!",
+                    TreeMode::RestoreWorkingDirectory(_) => "No items were restored!",
+                    _ => "No items were staged!",
+                };
+
+                println!("{}\n", Colour::Red.bold().paint(info));
             }
         }
     }
@@ -113,17 +176,17 @@ pub fn modify_trees(
 }
 
 /// Restore a file to its working directory or clean state.
+///
+/// Files that do not exist at `HEAD` were untracked before they were added to the
+/// index, so they are removed from the index instead.
 fn restore_file(
-    head_tree: &Tree,
+    head_tree: Option<&Tree>,
     index: &mut Index,
     relative_path: &Path,
     repo: &Repository,
     staged_files: &mut i32,
 ) -> Result<(), Error> {
-    if head_tree
-        .get_name(relative_path.to_str().unwrap_or("?"))
-        .is_some()
-    {
+    if head_tree.is_some_and(|head_tree| head_tree.get_path(relative_path).is_ok()) {
         let mut checkout_options = CheckoutBuilder::new();
         checkout_options.force();
         checkout_options.path(relative_path);
@@ -138,3 +201,18 @@ fn restore_file(
 
     Ok(())
 }
+
+/// Restore a file in the working directory to its state in the source tree. Unlike
+/// `restore_file`, the index is left untouched.
+fn restore_file_from_source(
+    source_tree: &Tree,
+    relative_path: &Path,
+    repo: &Repository,
+) -> Result<(), Error> {
+    let mut checkout_options = CheckoutBuilder::new();
+    checkout_options.force();
+    checkout_options.path(relative_path);
+    checkout_options.update_index(false);
+
+    repo.checkout_tree(source_tree.as_object(), Some(&mut checkout_options))
+}
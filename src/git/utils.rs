@@ -1,6 +1,9 @@
 //! Contains useful utilities that support Git functionality.
 
-use std::{ffi::OsStr, path::Path};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
 
 use crate::{errors::NomadError, style::models::NomadStyle};
 
@@ -36,6 +39,23 @@ pub fn get_repo(target_directory: &str) -> Option<Repository> {
     }
 }
 
+/// Get the number of commits the branch is ahead of and behind the upstream branch
+/// it tracks. Returns `None` if the branch does not track an upstream branch.
+pub fn get_ahead_behind(
+    branch: &Branch,
+    repo: &Repository,
+) -> Result<Option<(usize, usize)>, NomadError> {
+    let upstream_target = branch
+        .upstream()
+        .ok()
+        .and_then(|upstream| upstream.get().target());
+
+    match (branch.get().target(), upstream_target) {
+        (Some(local), Some(upstream)) => Ok(Some(repo.graph_ahead_behind(local, upstream)?)),
+        _ => Ok(None),
+    }
+}
+
 /// Try to get the current Git branch's name.
 pub fn get_repo_branch(repo: &Repository) -> Option<String> {
     if let Ok(reference) = repo.head() {
@@ -70,6 +90,17 @@ pub fn get_last_commit(repo: &Repository) -> Result<Commit, NomadError> {
     })
 }
 
+/// Get the absolute path to the root of the repository's working directory.
+pub fn get_repo_root(repo: &Repository) -> Result<PathBuf, NomadError> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| NomadError::Error(anyhow!("Git repository is bare!")))?;
+
+    Ok(workdir
+        .canonicalize()
+        .unwrap_or_else(|_| workdir.to_path_buf()))
+}
+
 /// Add color/style to the filename depending on its Git status.
 pub fn paint_git_item(
     filename: &str,
@@ -181,3 +212,72 @@ fn paint_with_highlight(
 
     filename
 }
+
+#[cfg(test)]
+pub mod test_utils {
+    use git2::{Oid, Repository, Signature, Time};
+    use tempfile::TempDir;
+
+    use std::fs::{create_dir_all, write};
+
+    /// Create an empty repository in a temporary directory. The directory is deleted
+    /// once the `TempDir` is dropped.
+    pub fn init_repo() -> (TempDir, Repository) {
+        let directory = TempDir::new().unwrap();
+        let repo = Repository::init(directory.path()).unwrap();
+
+        (directory, repo)
+    }
+
+    /// Write the files on top of the first parent's tree and commit them with the
+    /// summary. Each commit is a second newer than its newest parent, and `HEAD` is
+    /// detached at the new commit.
+    pub fn commit_files(
+        repo: &Repository,
+        files: &[(&str, &str)],
+        summary: &str,
+        parents: &[Oid],
+    ) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        let parents = parents
+            .iter()
+            .map(|parent| repo.find_commit(*parent).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut index = repo.index().unwrap();
+        match parents.first() {
+            Some(parent) => index.read_tree(&parent.tree().unwrap()).unwrap(),
+            None => index.clear().unwrap(),
+        }
+        for (path, content) in files {
+            let file = workdir.join(path);
+            create_dir_all(file.parent().unwrap()).unwrap();
+            write(&file, content).unwrap();
+
+            index.add_path(path.as_ref()).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+        let time = parents
+            .iter()
+            .map(|parent| parent.time().seconds() + 1)
+            .max()
+            .unwrap_or(1_600_000_000);
+        let signature = Signature::new("Alice", "alice@example.com", &Time::new(time, 0)).unwrap();
+
+        let commit = repo
+            .commit(
+                None,
+                &signature,
+                &signature,
+                summary,
+                &tree,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        repo.set_head_detached(commit).unwrap();
+
+        commit
+    }
+}
@@ -13,7 +13,7 @@ use term_table::{
 };
 use tokei::{Config, Language, Languages, Sort};
 
-use crate::cli::global::GlobalArgs;
+use crate::{cli::global::GlobalArgs, models::JsonTokei};
 
 use self::{format::tree_stats_from_report, utils::get_file_report};
 
@@ -55,6 +55,16 @@ pub fn loc_in_file(args: &GlobalArgs, file_path: &str, tokei: &Language) -> Vec<
     formatted_stats
 }
 
+/// Get the `CodeStats` for a single file in a form that may be serialized to JSON.
+pub fn loc_json_in_file(file_path: &str, tokei: &Language) -> Option<JsonTokei> {
+    get_file_report(&tokei.children, PathBuf::from(file_path)).map(|report| JsonTokei {
+        blanks: report.stats.blanks,
+        code: report.stats.code,
+        comments: report.stats.comments,
+        lines: report.stats.lines(),
+    })
+}
+
 /// Summarize the `Tokei` stats for this directory.
 /// Sort summary by lines of code, descending.
 pub fn run_tokei(target_directory: &str) {
@@ -5,6 +5,7 @@
 // NOTE - Clippy allowed lints are subject to change in the future.
 #![allow(clippy::field_reassign_with_default)]
 #![allow(clippy::format_in_format_args)]
+#![allow(clippy::large_enum_variant)]
 #![allow(clippy::manual_map)]
 #![allow(clippy::needless_range_loop)]
 #![allow(clippy::too_many_arguments)]
@@ -27,12 +28,15 @@ mod utils;
 
 use cli::{get_args, SubCommands};
 use config::toml::parse_config;
+use git::revision::{json_revision, walk_revision};
 use loc::run_tokei;
 use releases::{check_for_update, update_self};
 use style::settings::process_settings;
 use switches::{config::run_config, filetype::run_filetypes, git::run_git, release::run_releases};
 use syntect::{highlighting::ThemeSet, parsing::SyntaxSet};
-use traverse::{modes::NomadMode, utils::build_walker, walk_directory};
+use traverse::{
+    json_directory, modes::NomadMode, stream_directory, utils::build_walk_builder, walk_directory,
+};
 use ui::{enter_rootless_mode, ExitMode};
 use utils::{
     bat::run_bat,
@@ -191,9 +195,51 @@ fn main() -> Result<(), NomadError> {
                         }
                     }
                 }
+            } else if args.global.modifiers.rev.is_some() {
+                // Run `nomad` against a Git revision.
+                if args.global.json {
+                    if let Err(error) = json_revision(&args.global, &nomad_style, &target_directory)
+                    {
+                        paint_error(error);
+                    }
+                } else {
+                    match walk_revision(&args.global, &nomad_style, &target_directory) {
+                        Ok((tree, config, json_item)) => {
+                            if let Some(export) = args.global.export {
+                                if let Err(error) = export_tree(
+                                    config,
+                                    args.global.export_format,
+                                    ExportMode::Normal,
+                                    &export,
+                                    &args.global.graph,
+                                    json_item,
+                                    &nomad_style,
+                                    tree,
+                                ) {
+                                    paint_error(error);
+                                }
+                            }
+                        }
+                        Err(error) => paint_error(error),
+                    }
+                }
+            } else if args.global.json {
+                // Display `nomad` in normal mode as JSON.
+                if let Err(error) =
+                    json_directory(&args.global, &nomad_style, &target_directory, None)
+                {
+                    paint_error(error);
+                }
+            } else if args.global.stream && args.global.export.is_none() {
+                // Stream `nomad` in normal mode.
+                if let Err(error) =
+                    stream_directory(&args.global, &nomad_style, &target_directory, None)
+                {
+                    paint_error(error);
+                }
             } else {
                 // Run `nomad` in normal mode.
-                match build_walker(&args.global, &target_directory, None) {
+                match build_walk_builder(&args.global, &target_directory, None) {
                     Ok(mut walker) => {
                         match walk_directory(
                             &args.global,
@@ -202,11 +248,18 @@ fn main() -> Result<(), NomadError> {
                             &target_directory,
                             &mut walker,
                         ) {
-                            Ok((tree, config, _)) => {
+                            Ok((tree, config, _, json_item)) => {
                                 if let Some(export) = args.global.export {
-                                    if let Err(error) =
-                                        export_tree(config, ExportMode::Normal, &export, tree)
-                                    {
+                                    if let Err(error) = export_tree(
+                                        config,
+                                        args.global.export_format,
+                                        ExportMode::Normal,
+                                        &export,
+                                        &args.global.graph,
+                                        json_item,
+                                        &nomad_style,
+                                        tree,
+                                    ) {
                                         paint_error(error);
                                     }
                                 }
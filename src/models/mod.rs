@@ -5,10 +5,239 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 
 /// Store all directory items.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Contents {
     /// Contains labeled directory paths.
     pub labeled: HashMap<String, String>,
     /// Contains numbered directory items.
     pub numbered: HashMap<String, String>,
+    /// Contains the line and column of the first content match in each file that
+    /// was searched with `--grep`, keyed by the file's path.
+    #[serde(default)]
+    pub positions: HashMap<String, (usize, usize)>,
+    /// Contains the SHA of the Git revision the tree was built from, if it was built
+    /// with `--rev`.
+    #[serde(default)]
+    pub revision: Option<String>,
+    /// Contains the two sides of the diff if the tree was built with `git diff --tree`.
+    #[serde(default)]
+    pub diff: Option<DiffRange>,
+    /// Contains the numbered branch names from the last branch tree or list.
+    #[serde(default)]
+    pub branches: HashMap<String, String>,
+}
+
+/// The two sides that are compared in a Git diff. Revisions are stored as full SHAs.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum DiffRange {
+    /// Compare two commits.
+    Commits {
+        /// The SHA of the older commit.
+        old: String,
+        /// The SHA of the newer commit.
+        new: String,
+    },
+    /// Compare a commit with the index.
+    Staged {
+        /// The SHA of the commit.
+        old: String,
+    },
+    /// Compare a commit with the working directory.
+    WorkingDirectory {
+        /// The SHA of the commit.
+        old: String,
+    },
+}
+
+/// The Git status of a tree item, independent of how its marker is styled.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitStatus {
+    /// The file contains merge conflicts.
+    Conflicted,
+    /// The file was deleted.
+    Deleted,
+    /// The file is ignored by Git. This is only set if ignored files were requested.
+    Ignored,
+    /// The file was modified.
+    Modified,
+    /// The file was renamed.
+    Renamed,
+    /// The file was added to the index.
+    StagedAdded,
+    /// The file was deleted from the index.
+    StagedDeleted,
+    /// The file was modified in the index.
+    StagedModified,
+    /// The file was renamed in the index.
+    StagedRenamed,
+    /// The file's type was changed in the index.
+    StagedTypechanged,
+    /// The file's type was changed.
+    Typechanged,
+    /// The file is not tracked.
+    Untracked,
+}
+
+/// Contains an exported JSON tree and the mode in which it was built.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JsonExport {
+    /// The mode in which `nomad` was run.
+    pub mode: String,
+    /// The filetypes that were matched or negated in filetype mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filetypes: Option<Vec<String>>,
+    /// The globs that were matched or negated in filetype mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub globs: Option<Vec<String>>,
+    /// The exported tree.
+    pub tree: Option<JsonItem>,
+}
+
+/// Contains a tree item when the tree is serialized to JSON.
+///
+/// Fields are serialized in the order they are declared, so the identifying fields
+/// are declared first and nested items are declared last.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JsonItem {
+    /// The name of the item.
+    pub name: String,
+    /// The absolute path to the item.
+    pub path: String,
+    /// Indicates whether this is a directory or a file.
+    #[serde(rename = "type")]
+    pub item_type: JsonItemType,
+    /// The depth of the item relative to the root of the tree.
+    pub depth: i32,
+    /// The Git status of the file if it contains changes.
+    pub git_status: Option<GitStatus>,
+    /// The directory's label.
+    pub label: Option<String>,
+    /// The file's number.
+    pub number: Option<usize>,
+    /// The start and end of the pattern match in the file's path relative to the
+    /// root of the tree.
+    pub matched: Option<MatchSpan>,
+    /// The item's metadata. This is only set if metadata was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<JsonMetadata>,
+    /// The file's code statistics. This is only set if `tokei` stats were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokei: Option<JsonTokei>,
+    /// The commit that last changed the item. This is only set if `--last-commit`
+    /// was passed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_commit: Option<JsonLastCommit>,
+    /// The number of commits that touched the item and the lines they changed. This is
+    /// only set for `git churn` trees.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub churn: Option<JsonChurn>,
+    /// The authors that own the most lines in the item. This is only set for
+    /// `git owners` trees.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owners: Option<Vec<JsonOwner>>,
+    /// The lines in the file that matched the content search. This is only set if
+    /// `--grep` was used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_matches: Option<Vec<LineMatch>>,
+    /// The items within this directory. This is only set for directories.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<JsonItem>>,
+}
+
+/// Indicates whether a JSON tree item is a directory or a file.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonItemType {
+    /// The item is a directory.
+    Directory,
+    /// The item is a file.
+    File,
+}
+
+/// Contains the start and end of a pattern match.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MatchSpan {
+    /// The byte offset at which the match starts.
+    pub start: usize,
+    /// The byte offset at which the match ends.
+    pub end: usize,
+}
+
+/// Contains a line in a file that matched the content search.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LineMatch {
+    /// The line number, starting at 1.
+    pub line_number: usize,
+    /// The column of the first match in the line, starting at 1. Columns are
+    /// counted in characters rather than bytes.
+    pub column: usize,
+    /// The line's text.
+    pub line: String,
+    /// The start and end of every match in the line.
+    pub matches: Vec<MatchSpan>,
+}
+
+/// Contains an item's metadata when the tree is serialized to JSON.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JsonMetadata {
+    /// The name of the group that owns the item. This is only available on UNIX systems.
+    pub group: Option<String>,
+    /// The last modified time as a UNIX timestamp.
+    pub last_modified: Option<u64>,
+    /// The item's permission bits, ie. `-rw-r--r--`. This is only available on UNIX systems.
+    pub mode: Option<String>,
+    /// The size of the item in bytes.
+    pub size: u64,
+    /// The name of the user that owns the item. This is only available on UNIX systems.
+    pub user: Option<String>,
+}
+
+/// Contains the churn of an item when the tree is serialized to JSON.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JsonChurn {
+    /// The number of commits that touched the item.
+    pub commits: usize,
+    /// The number of lines those commits added.
+    pub added: usize,
+    /// The number of lines those commits deleted.
+    pub deleted: usize,
+}
+
+/// Contains the commit that last changed an item when the tree is serialized to JSON.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JsonLastCommit {
+    /// The abbreviated SHA of the commit.
+    pub id: String,
+    /// The name of the commit's author.
+    pub author: String,
+    /// The time the commit was authored as a UNIX timestamp.
+    pub time: i64,
+    /// The first line of the commit message.
+    pub summary: String,
+}
+
+/// Contains an owner of the lines in an item when the tree is serialized to JSON.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JsonOwner {
+    /// The name or email of the owner.
+    pub name: String,
+    /// The number of lines the owner owns.
+    pub lines: usize,
+    /// The percentage of the item's lines the owner owns.
+    pub percentage: f64,
+}
+
+/// Contains a file's code statistics when the tree is serialized to JSON.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JsonTokei {
+    /// The number of blank lines in the file.
+    pub blanks: usize,
+    /// The lines of code in the file.
+    pub code: usize,
+    /// The number of comments in the file.
+    pub comments: usize,
+    /// The total number of lines in the file.
+    pub lines: usize,
 }
@@ -1,5 +1,7 @@
 //! Struct used to store colors/styles for `nomad`.
 
+use crate::traverse::modes::SortMode;
+
 use ansi_term::{Colour, Style};
 use ptree::print_config::UTF_CHARS;
 use tui::style::Color;
@@ -26,6 +28,10 @@ pub struct GitStyle {
     pub deleted_color: Style,
     /// The string that marks a deleted file.
     pub deleted_marker: String,
+    /// The color of the ignored file's marker.
+    pub ignored_color: Style,
+    /// The string that marks an ignored file.
+    pub ignored_marker: String,
     /// The color of the modified file's marker.
     pub modified_color: Style,
     /// The string that marks a modified file.
@@ -67,6 +73,8 @@ pub struct GitStyle {
 /// Contains styles for the tree itself.
 #[derive(Debug)]
 pub struct TreeStyle {
+    /// Display directories before files.
+    pub dirs_first: bool,
     /// Contains the indentation setting.
     pub indent: usize,
     /// Contains indent characters for the tree itself.
@@ -75,10 +83,18 @@ pub struct TreeStyle {
     pub item_colors: ItemColors,
     /// Contains colors for the tree labels.
     pub label_colors: LabelColors,
+    /// The maximum number of items displayed within each directory.
+    pub max_items: Option<usize>,
     /// Contains the padding setting.
     pub padding: usize,
     /// The color styles for all things regex.
     pub regex: TreeRegexStyle,
+    /// Reverse the sort order.
+    pub reverse: bool,
+    /// The order in which the items within each directory are sorted.
+    pub sort: Option<SortMode>,
+    /// The number of threads used to walk directories.
+    pub threads: Option<usize>,
 }
 
 /// Contains the colors for items in the tree.
@@ -171,6 +187,8 @@ impl Default for NomadStyle {
                 conflicted_marker: "!".to_string(),
                 deleted_color: Colour::Red.bold(),
                 deleted_marker: "D".to_string(),
+                ignored_color: Colour::Fixed(240).bold(),
+                ignored_marker: "I".to_string(),
                 modified_color: Colour::Fixed(172).bold(),
                 modified_marker: "M".to_string(),
                 renamed_color: Colour::Fixed(172).bold(),
@@ -191,6 +209,7 @@ impl Default for NomadStyle {
                 untracked_marker: "U".to_string(),
             },
             tree: TreeStyle {
+                dirs_first: false,
                 indent: 4,
                 indent_chars: IndentStyles {
                     down: UTF_CHARS.down.to_string(),
@@ -206,10 +225,14 @@ impl Default for NomadStyle {
                     item_labels: Colour::Fixed(068).bold(),
                     directory_labels: Colour::Fixed(068).bold(),
                 },
+                max_items: None,
                 padding: 1,
                 regex: TreeRegexStyle {
                     match_color: Colour::Fixed(033).bold(),
                 },
+                reverse: false,
+                sort: None,
+                threads: None,
             },
             tui: TUIStyle {
                 border_color: Color::Indexed(033),
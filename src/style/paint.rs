@@ -15,6 +15,9 @@ pub fn process_git_settings(nomad_style: &mut NomadStyle, git_settings: &TreeGit
         if let Some(color) = &colors.deleted_color {
             nomad_style.git.deleted_color = convert_to_ansi_style(&color.to_lowercase());
         }
+        if let Some(color) = &colors.ignored_color {
+            nomad_style.git.ignored_color = convert_to_ansi_style(&color.to_lowercase());
+        }
         if let Some(color) = &colors.modified_color {
             nomad_style.git.modified_color = convert_to_ansi_style(&color.to_lowercase());
         }
@@ -51,6 +54,9 @@ pub fn process_git_settings(nomad_style: &mut NomadStyle, git_settings: &TreeGit
         if let Some(marker) = &markers.deleted_marker {
             nomad_style.git.deleted_marker = marker.to_string();
         }
+        if let Some(marker) = &markers.ignored_marker {
+            nomad_style.git.ignored_marker = marker.to_string();
+        }
         if let Some(marker) = &markers.modified_marker {
             nomad_style.git.modified_marker = marker.to_string();
         }
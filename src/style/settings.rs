@@ -4,7 +4,12 @@ use ansi_term::Colour;
 use ptree::print_config::UTF_CHARS;
 use tui::style::Color;
 
-use crate::config::models::{IndentCharacters, NomadConfig};
+use crate::{
+    config::models::{IndentCharacters, NomadConfig},
+    traverse::modes::SortMode,
+};
+
+use std::str::FromStr;
 
 use super::{
     models::NomadStyle,
@@ -21,6 +26,14 @@ pub fn process_settings(nomad_config: NomadConfig) -> NomadStyle {
     if let Some(tree_settings) = nomad_config.tree {
         nomad_style.tree.indent = tree_settings.indent.unwrap_or(4);
         nomad_style.tree.padding = tree_settings.padding.unwrap_or(1);
+        nomad_style.tree.threads = tree_settings.threads;
+        nomad_style.tree.max_items = tree_settings.max_items;
+
+        nomad_style.tree.dirs_first = tree_settings.dirs_first.unwrap_or(false);
+        nomad_style.tree.reverse = tree_settings.reverse.unwrap_or(false);
+        nomad_style.tree.sort = tree_settings
+            .sort
+            .and_then(|sort_mode| SortMode::from_str(&sort_mode).ok());
 
         if let Some(label_settings) = tree_settings.labels {
             if let Some(color) = label_settings.item_labels {
@@ -6,8 +6,10 @@ use crate::{
     cli::filetype::FileTypeOptions,
     style::models::NomadStyle,
     traverse::{
+        json_directory,
         modes::NomadMode,
-        utils::{build_types, build_walker, TypeOption},
+        stream_directory,
+        utils::{build_types, build_walk_builder, TypeOption},
         walk_directory,
     },
     utils::{
@@ -35,34 +37,63 @@ pub fn run_filetypes(
                 TypeOption::Match,
             ) {
                 Ok(types) => {
-                    match build_walker(&match_options.general, target_directory, Some(types)) {
-                        Ok(mut walker) => {
-                            match walk_directory(
-                                &match_options.general,
-                                NomadMode::Normal,
-                                nomad_style,
-                                target_directory,
-                                &mut walker,
-                            ) {
-                                Ok((tree, config, _)) => {
-                                    if let Some(export) = &match_options.general.export {
-                                        if let Err(error) = export_tree(
-                                            config,
-                                            ExportMode::Filetype(
-                                                &match_options.filetypes,
-                                                &match_options.globs,
-                                            ),
-                                            export,
-                                            tree,
-                                        ) {
-                                            paint_error(error);
+                    if match_options.general.json {
+                        if let Err(error) = json_directory(
+                            &match_options.general,
+                            nomad_style,
+                            target_directory,
+                            Some(types),
+                        ) {
+                            paint_error(error);
+                        }
+                    } else if match_options.general.stream && match_options.general.export.is_none()
+                    {
+                        if let Err(error) = stream_directory(
+                            &match_options.general,
+                            nomad_style,
+                            target_directory,
+                            Some(types),
+                        ) {
+                            paint_error(error);
+                        }
+                    } else {
+                        match build_walk_builder(
+                            &match_options.general,
+                            target_directory,
+                            Some(types),
+                        ) {
+                            Ok(mut walker) => {
+                                match walk_directory(
+                                    &match_options.general,
+                                    NomadMode::Normal,
+                                    nomad_style,
+                                    target_directory,
+                                    &mut walker,
+                                ) {
+                                    Ok((tree, config, _, json_item)) => {
+                                        if let Some(export) = &match_options.general.export {
+                                            if let Err(error) = export_tree(
+                                                config,
+                                                match_options.general.export_format,
+                                                ExportMode::Filetype(
+                                                    &match_options.filetypes,
+                                                    &match_options.globs,
+                                                ),
+                                                export,
+                                                &match_options.general.graph,
+                                                json_item,
+                                                nomad_style,
+                                                tree,
+                                            ) {
+                                                paint_error(error);
+                                            }
                                         }
                                     }
+                                    Err(error) => paint_error(error),
                                 }
-                                Err(error) => paint_error(error),
                             }
+                            Err(error) => paint_error(error),
                         }
-                        Err(error) => paint_error(error),
                     }
                 }
                 Err(error) => paint_error(error),
@@ -76,34 +107,64 @@ pub fn run_filetypes(
                 TypeOption::Negate,
             ) {
                 Ok(types) => {
-                    match build_walker(&negate_options.general, target_directory, Some(types)) {
-                        Ok(mut walker) => {
-                            match walk_directory(
-                                &negate_options.general,
-                                NomadMode::Normal,
-                                nomad_style,
-                                target_directory,
-                                &mut walker,
-                            ) {
-                                Ok((tree, config, _)) => {
-                                    if let Some(export) = &negate_options.general.export {
-                                        if let Err(error) = export_tree(
-                                            config,
-                                            ExportMode::Filetype(
-                                                &negate_options.filetypes,
-                                                &negate_options.globs,
-                                            ),
-                                            export,
-                                            tree,
-                                        ) {
-                                            paint_error(error);
+                    if negate_options.general.json {
+                        if let Err(error) = json_directory(
+                            &negate_options.general,
+                            nomad_style,
+                            target_directory,
+                            Some(types),
+                        ) {
+                            paint_error(error);
+                        }
+                    } else if negate_options.general.stream
+                        && negate_options.general.export.is_none()
+                    {
+                        if let Err(error) = stream_directory(
+                            &negate_options.general,
+                            nomad_style,
+                            target_directory,
+                            Some(types),
+                        ) {
+                            paint_error(error);
+                        }
+                    } else {
+                        match build_walk_builder(
+                            &negate_options.general,
+                            target_directory,
+                            Some(types),
+                        ) {
+                            Ok(mut walker) => {
+                                match walk_directory(
+                                    &negate_options.general,
+                                    NomadMode::Normal,
+                                    nomad_style,
+                                    target_directory,
+                                    &mut walker,
+                                ) {
+                                    Ok((tree, config, _, json_item)) => {
+                                        if let Some(export) = &negate_options.general.export {
+                                            if let Err(error) = export_tree(
+                                                config,
+                                                negate_options.general.export_format,
+                                                ExportMode::Filetype(
+                                                    &negate_options.filetypes,
+                                                    &negate_options.globs,
+                                                ),
+                                                export,
+                                                &negate_options.general.graph,
+                                                json_item,
+                                                nomad_style,
+                                                tree,
+                                            ) {
+                                                paint_error(error);
+                                            }
                                         }
                                     }
+                                    Err(error) => paint_error(error),
                                 }
-                                Err(error) => paint_error(error),
                             }
+                            Err(error) => paint_error(error),
                         }
-                        Err(error) => paint_error(error),
                     }
                 }
                 Err(error) => paint_error(error),
@@ -1,20 +1,24 @@
 //! Executing Git subcommands.
 
 use crate::{
-    cli::{git::GitOptions, Args},
+    cli::{git::GitOptions, global::GraphArgs, Args},
     errors::NomadError,
     git::{
         blame::bat_blame,
-        branch::display_branches,
+        branch::{create_branch, delete_branches, display_branches, rename_branch, switch_branch},
+        churn::display_churn_tree,
         commit::commit_changes,
-        diff::{bat_diffs, get_repo_diffs},
+        diff::{bat_diffs, display_diff_tree, get_diff_range, get_repo_diffs},
+        hunks::stage_hunks,
+        owners::display_owners_tree,
         status::{display_commits_ahead, display_status_tree},
         trees::{modify_trees, TreeMode},
-        utils::{get_repo, get_repo_branch},
+        utils::{get_repo, get_repo_branch, get_repo_root},
     },
     style::models::NomadStyle,
     utils::{
         export::{export_tree, ExportMode},
+        open::get_deserialized_json,
         paint::paint_error,
         search::{indiscriminate_search, SearchMode},
     },
@@ -31,6 +35,17 @@ pub fn run_git(
 ) {
     if let Some(repo) = get_repo(target_directory) {
         match git_command {
+            GitOptions::Add(add_options) if add_options.patch => {
+                if let Err(error) = stage_hunks(
+                    args,
+                    &add_options.item_labels,
+                    nomad_style,
+                    &repo,
+                    target_directory,
+                ) {
+                    paint_error(error);
+                }
+            }
             GitOptions::Add(add_options) => {
                 let stage_mode = match add_options.all {
                     true => TreeMode::StageAll,
@@ -89,14 +104,70 @@ pub fn run_git(
                 }
                 Err(_) => paint_error(NomadError::GitBlameError),
             },
+            GitOptions::Branch(branch_options) if branch_options.create.is_some() => {
+                if let Err(error) = create_branch(
+                    branch_options.create.as_deref().unwrap_or("?"),
+                    branch_options.from.as_deref(),
+                    &repo,
+                ) {
+                    paint_error(error);
+                }
+            }
+            GitOptions::Branch(branch_options) if !branch_options.delete.is_empty() => {
+                if let Err(error) =
+                    delete_branches(&branch_options.delete, branch_options.force, &repo)
+                {
+                    paint_error(error);
+                }
+            }
+            GitOptions::Branch(branch_options) if !branch_options.rename.is_empty() => {
+                if let Err(error) =
+                    rename_branch(&branch_options.rename[0], &branch_options.rename[1], &repo)
+                {
+                    paint_error(error);
+                }
+            }
             GitOptions::Branch(branch_options) => {
                 match display_branches(branch_options, nomad_style, &repo, target_directory) {
                     Ok(tree_items) => {
                         if let Some((tree, config, _)) = tree_items {
                             if let Some(export) = &branch_options.export {
-                                if let Err(error) =
-                                    export_tree(config, ExportMode::GitBranch, export, tree)
-                                {
+                                if let Err(error) = export_tree(
+                                    config,
+                                    branch_options.export_format,
+                                    ExportMode::GitBranch,
+                                    export,
+                                    &GraphArgs {
+                                        graph_depth: None,
+                                        graph_max_nodes: None,
+                                    },
+                                    None,
+                                    nomad_style,
+                                    tree,
+                                ) {
+                                    paint_error(error);
+                                }
+                            }
+                        }
+                    }
+                    Err(error) => paint_error(error),
+                }
+            }
+            GitOptions::Churn(churn_options) => {
+                match display_churn_tree(churn_options, nomad_style, &repo, target_directory) {
+                    Ok(tree_items) => {
+                        if let Some((tree, config, json_item)) = tree_items {
+                            if let Some(export) = &churn_options.export {
+                                if let Err(error) = export_tree(
+                                    config,
+                                    churn_options.export_format,
+                                    ExportMode::GitChurn,
+                                    export,
+                                    &churn_options.graph,
+                                    json_item,
+                                    nomad_style,
+                                    tree,
+                                ) {
                                     paint_error(error);
                                 }
                             }
@@ -105,46 +176,117 @@ pub fn run_git(
                     Err(error) => paint_error(error),
                 }
             }
-            GitOptions::Commit { message } => {
-                if let Err(error) = commit_changes(message, &repo) {
+            GitOptions::Commit(commit_options) => {
+                if let Err(error) =
+                    commit_changes(commit_options, nomad_style, &repo, target_directory)
+                {
                     paint_error(error);
                 }
             }
-            GitOptions::Diff { item_labels } => match get_repo_diffs(&repo) {
-                Ok(diff) => {
-                    match indiscriminate_search(
-                        args,
-                        item_labels,
-                        nomad_style,
-                        Some(&repo),
-                        SearchMode::GitDiff,
-                        target_directory,
-                    ) {
-                        Some(found_items) => {
-                            if let Err(error) = bat_diffs(diff, Some(found_items), target_directory)
-                            {
+            GitOptions::Diff(diff_options) => {
+                // Labels refer to the last tree that was displayed, so the diff range of
+                // a diff tree is reused when its items are diffed.
+                let stored_range = match (&diff_options.tree, diff_options.staged) {
+                    (None, false) if !diff_options.item_labels.is_empty() => {
+                        get_deserialized_json()
+                            .ok()
+                            .and_then(|contents| contents.diff)
+                    }
+                    _ => None,
+                };
+                let search_mode = match stored_range {
+                    Some(_) => SearchMode::Normal,
+                    None => SearchMode::GitDiff,
+                };
+
+                match stored_range.map_or_else(
+                    || {
+                        get_diff_range(
+                            diff_options
+                                .tree
+                                .as_ref()
+                                .and_then(|range| range.as_deref()),
+                            &repo,
+                            diff_options.staged,
+                        )
+                    },
+                    Ok,
+                ) {
+                    Ok(diff_range) => {
+                        if diff_options.tree.is_some() {
+                            if let Err(error) = display_diff_tree(
+                                diff_options,
+                                &diff_range,
+                                nomad_style,
+                                &repo,
+                                target_directory,
+                            ) {
                                 paint_error(error);
                             }
+                        } else {
+                            match (get_repo_diffs(&diff_range, &repo), get_repo_root(&repo)) {
+                                (Ok(diff), Ok(repo_root)) => {
+                                    let found_items = indiscriminate_search(
+                                        args,
+                                        &diff_options.item_labels,
+                                        nomad_style,
+                                        Some(&repo),
+                                        search_mode,
+                                        target_directory,
+                                    );
+
+                                    if let Err(error) = bat_diffs(
+                                        diff,
+                                        found_items,
+                                        repo_root.to_str().unwrap_or("?"),
+                                    ) {
+                                        paint_error(error);
+                                    }
+                                }
+                                (Err(error), _) => paint_error(NomadError::GitError {
+                                    context: "Unable to get Git diff".into(),
+                                    source: error,
+                                }),
+                                (_, Err(error)) => paint_error(error),
+                            }
                         }
-                        None => {
-                            if let Err(error) = bat_diffs(diff, None, target_directory) {
-                                paint_error(error);
+                    }
+                    Err(error) => paint_error(error),
+                }
+            }
+            GitOptions::Owners(owners_options) => {
+                match display_owners_tree(owners_options, nomad_style, &repo, target_directory) {
+                    Ok(tree_items) => {
+                        if let Some((tree, config, json_item)) = tree_items {
+                            if let Some(export) = &owners_options.export {
+                                if let Err(error) = export_tree(
+                                    config,
+                                    owners_options.export_format,
+                                    ExportMode::GitOwners,
+                                    export,
+                                    &owners_options.graph,
+                                    json_item,
+                                    nomad_style,
+                                    tree,
+                                ) {
+                                    paint_error(error);
+                                }
                             }
                         }
                     }
+                    Err(error) => paint_error(error),
                 }
-                Err(error) => paint_error(NomadError::GitError {
-                    context: "Unable to get Git diff".into(),
-                    source: error,
-                }),
-            },
+            }
             GitOptions::Restore(restore_options) => {
                 if let Err(error) = modify_trees(
                     args,
                     &restore_options.item_labels,
                     nomad_style,
                     &repo,
-                    TreeMode::RestoreWorkingDirectory,
+                    match restore_options.staged {
+                        true => TreeMode::RestoreIndex(restore_options.source.clone()),
+                        false => TreeMode::RestoreWorkingDirectory(restore_options.source.clone()),
+                    },
                     target_directory,
                 ) {
                     paint_error(NomadError::GitError {
@@ -153,25 +295,49 @@ pub fn run_git(
                     });
                 }
             }
+            GitOptions::Reset(reset_options) => {
+                if let Err(error) = modify_trees(
+                    args,
+                    &reset_options.item_labels,
+                    nomad_style,
+                    &repo,
+                    TreeMode::RestoreIndex(None),
+                    target_directory,
+                ) {
+                    paint_error(NomadError::GitError {
+                        context: "Unable to unstage files!".to_string(),
+                        source: error,
+                    });
+                }
+            }
             GitOptions::Status(status_options) => {
-                if let Some(branch_name) = get_repo_branch(&repo) {
-                    println!(
-                        "\nOn branch: {}",
-                        Colour::Green.bold().paint(branch_name.to_string())
-                    );
+                if !status_options.json {
+                    if let Some(branch_name) = get_repo_branch(&repo) {
+                        println!(
+                            "\nOn branch: {}",
+                            Colour::Green.bold().paint(branch_name.to_string())
+                        );
 
-                    if let Err(error) = display_commits_ahead(&branch_name, &repo) {
-                        paint_error(error);
+                        if let Err(error) = display_commits_ahead(&branch_name, &repo) {
+                            paint_error(error);
+                        }
                     }
                 }
 
                 match display_status_tree(status_options, nomad_style, &repo, target_directory) {
                     Ok(tree_items) => {
-                        if let Some((tree, config)) = tree_items {
+                        if let Some((tree, config, json_item)) = tree_items {
                             if let Some(export) = &status_options.export {
-                                if let Err(error) =
-                                    export_tree(config, ExportMode::GitStatus, export, tree)
-                                {
+                                if let Err(error) = export_tree(
+                                    config,
+                                    status_options.export_format,
+                                    ExportMode::GitStatus,
+                                    export,
+                                    &status_options.graph,
+                                    json_item,
+                                    nomad_style,
+                                    tree,
+                                ) {
                                     paint_error(error);
                                 }
                             }
@@ -182,6 +348,13 @@ pub fn run_git(
                     }
                 }
             }
+            GitOptions::Switch(switch_options) => {
+                if let Err(error) =
+                    switch_branch(&switch_options.branch, switch_options.force, &repo)
+                {
+                    paint_error(error);
+                }
+            }
         }
     } else {
         paint_error(NomadError::Error(anyhow!("Cannot run Git commands here!")));
@@ -0,0 +1,411 @@
+//! Filtering files by their metadata.
+
+use crate::{cli::global::GlobalArgs, errors::NomadError};
+
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use ignore::DirEntry;
+
+use std::{
+    path::Path,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The number of seconds in each unit that may be used in relative times, ie. `2d`.
+const TIME_UNITS: [(&str, u64); 5] = [
+    ("s", 1),
+    ("m", 60),
+    ("h", 60 * 60),
+    ("d", 24 * 60 * 60),
+    ("w", 7 * 24 * 60 * 60),
+];
+
+/// The number of bytes in each unit that may be used in sizes, ie. `10M`. Units
+/// without an `i` are powers of 1000 to match the sizes displayed in the tree.
+const SIZE_UNITS: [(&str, u64); 13] = [
+    ("", 1),
+    ("b", 1),
+    ("k", 1_000),
+    ("kb", 1_000),
+    ("kib", 1_024),
+    ("m", 1_000_000),
+    ("mb", 1_000_000),
+    ("mib", 1_048_576),
+    ("g", 1_000_000_000),
+    ("gb", 1_000_000_000),
+    ("gib", 1_073_741_824),
+    ("t", 1_000_000_000_000),
+    ("tb", 1_000_000_000_000),
+];
+
+/// A point in time passed into `--newer` or `--older`.
+///
+/// This may be a time relative to now, ie. `2d` or `3h`, or a date, ie. `2026-01-01`
+/// or `2026-01-01T12:00:00`. Dates are in the local timezone.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timestamp(pub SystemTime);
+
+impl FromStr for Timestamp {
+    type Err = String;
+
+    /// Convert the value passed into `--newer` or `--older` into a `Timestamp`.
+    fn from_str(timestamp: &str) -> Result<Self, Self::Err> {
+        let trimmed = timestamp.trim();
+
+        let digits = trimmed
+            .find(|character: char| !character.is_ascii_digit())
+            .unwrap_or(trimmed.len());
+        if let (Ok(amount), Some((_, seconds))) = (
+            trimmed[..digits].parse::<u64>(),
+            TIME_UNITS
+                .iter()
+                .find(|(unit, _)| *unit == trimmed[digits..].to_lowercase()),
+        ) {
+            return amount
+                .checked_mul(*seconds)
+                .and_then(|seconds| SystemTime::now().checked_sub(Duration::from_secs(seconds)))
+                .map(Timestamp)
+                .ok_or_else(|| format!("{timestamp} is too far in the past"));
+        }
+
+        let date_time = NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%dT%H:%M:%S")
+            .or_else(|_| NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%d %H:%M:%S"))
+            .or_else(|_| {
+                NaiveDate::parse_from_str(trimmed, "%Y-%m-%d")
+                    .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default())
+            })
+            .map_err(|_| {
+                format!(
+                    "{timestamp} is not a relative time (ie. `2d`) or a date (ie. `2026-01-01`)"
+                )
+            })?;
+
+        date_time
+            .and_local_timezone(Local)
+            .earliest()
+            .and_then(|date_time| u64::try_from(date_time.timestamp()).ok())
+            .map(|seconds| Timestamp(UNIX_EPOCH + Duration::from_secs(seconds)))
+            .ok_or_else(|| format!("{timestamp} is not a valid local time"))
+    }
+}
+
+/// A number of bytes passed into `--min-size`, ie. `10M`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FileSize(pub u64);
+
+impl FromStr for FileSize {
+    type Err = String;
+
+    /// Convert a size such as `512`, `1.5K`, or `10MiB` into a number of bytes.
+    fn from_str(size: &str) -> Result<Self, Self::Err> {
+        let trimmed = size.trim();
+
+        let number_length = trimmed
+            .find(|character: char| !character.is_ascii_digit() && character != '.')
+            .unwrap_or(trimmed.len());
+
+        match (
+            trimmed[..number_length].parse::<f64>(),
+            SIZE_UNITS
+                .iter()
+                .find(|(unit, _)| *unit == trimmed[number_length..].trim().to_lowercase()),
+        ) {
+            (Ok(number), Some((_, bytes))) => {
+                let bytes = (number * *bytes as f64).round();
+                if bytes < u64::MAX as f64 {
+                    Ok(FileSize(bytes as u64))
+                } else {
+                    Err(format!("{size} is too large"))
+                }
+            }
+            _ => Err(format!(
+                "{size} is not a valid size. Use a number followed by an optional unit, ie. `10M`"
+            )),
+        }
+    }
+}
+
+/// An inclusive range of sizes passed into `--size`, ie. `1K..5M`. Either end of the
+/// range may be left out, ie. `..5M`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SizeRange {
+    /// The smallest size in the range.
+    pub min: Option<u64>,
+    /// The largest size in the range.
+    pub max: Option<u64>,
+}
+
+impl FromStr for SizeRange {
+    type Err = String;
+
+    /// Convert the value passed into `--size` into a `SizeRange`.
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let (min, max) = range
+            .split_once("..")
+            .ok_or_else(|| format!("{range} is not a range. Use `MIN..MAX`, ie. `1K..5M`"))?;
+
+        let parse_bound = |bound: &str| -> Result<Option<u64>, String> {
+            if bound.trim().is_empty() {
+                Ok(None)
+            } else {
+                bound.parse::<FileSize>().map(|size| Some(size.0))
+            }
+        };
+
+        match (parse_bound(min)?, parse_bound(max)?) {
+            (None, None) => Err(format!(
+                "{range} does not contain a minimum or maximum size"
+            )),
+            (Some(min), Some(max)) if min > max => Err(format!(
+                "The minimum size in {range} is larger than the maximum size"
+            )),
+            (min, max) => Ok(SizeRange { min, max }),
+        }
+    }
+}
+
+/// Contains the metadata filters that files must pass to be displayed in the tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MetadataFilters {
+    /// Files must be at least this many bytes.
+    pub min_size: Option<u64>,
+    /// Files must be at most this many bytes.
+    pub max_size: Option<u64>,
+    /// Files must have been modified after this time.
+    pub newer: Option<SystemTime>,
+    /// Files must have been modified before this time.
+    pub older: Option<SystemTime>,
+}
+
+impl MetadataFilters {
+    /// Check whether the entry passes every filter. Directories always pass so that
+    /// the files within them may be checked.
+    pub fn matches(&self, entry: &DirEntry) -> bool {
+        if !matches!(entry.file_type(), Some(file_type) if !file_type.is_dir()) {
+            return true;
+        }
+
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => return false,
+        };
+
+        if let Some(min_size) = self.min_size {
+            if metadata.len() < min_size {
+                return false;
+            }
+        }
+        if let Some(max_size) = self.max_size {
+            if metadata.len() > max_size {
+                return false;
+            }
+        }
+
+        if self.newer.is_some() || self.older.is_some() {
+            let modified = match metadata.modified() {
+                Ok(modified) => modified,
+                Err(_) => return false,
+            };
+
+            if let Some(newer) = self.newer {
+                if modified <= newer {
+                    return false;
+                }
+            }
+            if let Some(older) = self.older {
+                if modified >= older {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// Get the metadata filters from the client's CLI parameters. Returns `None` if no
+/// filters were set.
+///
+/// If both `--newer` and `--newer-than` are provided, files have to be newer than
+/// the later of the two.
+pub fn get_metadata_filters(args: &GlobalArgs) -> Result<Option<MetadataFilters>, NomadError> {
+    let newer_than = match args.modifiers.newer_than {
+        Some(ref path) => Some(
+            Path::new(path)
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .map_err(|error| {
+                    NomadError::PathError(format!(
+                        "Could not get the last modified time of {path}: {error}"
+                    ))
+                })?,
+        ),
+        None => None,
+    };
+
+    let (size_min, size_max) = args
+        .modifiers
+        .size
+        .map_or((None, None), |range| (range.min, range.max));
+
+    let filters = MetadataFilters {
+        min_size: args.modifiers.min_size.map(|size| size.0).max(size_min),
+        max_size: size_max,
+        newer: args
+            .modifiers
+            .newer
+            .map(|timestamp| timestamp.0)
+            .max(newer_than),
+        older: args.modifiers.older.map(|timestamp| timestamp.0),
+    };
+
+    if filters == MetadataFilters::default() {
+        Ok(None)
+    } else {
+        Ok(Some(filters))
+    }
+}
+
+#[cfg(test)]
+mod test_filters {
+    use super::*;
+
+    /// The number of seconds between `SystemTime::now()` and the `Timestamp`.
+    fn seconds_ago(timestamp: Timestamp) -> u64 {
+        SystemTime::now()
+            .duration_since(timestamp.0)
+            .unwrap_or_default()
+            .as_secs()
+    }
+
+    #[test]
+    fn test_timestamp_relative_units() {
+        for (timestamp, seconds) in [
+            ("30s", 30),
+            ("5m", 5 * 60),
+            ("3h", 3 * 60 * 60),
+            ("2d", 2 * 24 * 60 * 60),
+            ("2D", 2 * 24 * 60 * 60),
+            ("1w", 7 * 24 * 60 * 60),
+        ] {
+            let ago = seconds_ago(timestamp.parse::<Timestamp>().unwrap());
+
+            assert!((seconds..seconds + 5).contains(&ago), "{timestamp}");
+        }
+    }
+
+    #[test]
+    fn test_timestamp_dates() {
+        let expected = NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .earliest()
+            .unwrap()
+            .timestamp() as u64;
+
+        assert_eq!(
+            "2026-01-01".parse::<Timestamp>(),
+            Ok(Timestamp(UNIX_EPOCH + Duration::from_secs(expected)))
+        );
+        assert_eq!(
+            "2026-01-01T00:00:30".parse::<Timestamp>(),
+            Ok(Timestamp(UNIX_EPOCH + Duration::from_secs(expected + 30)))
+        );
+        assert_eq!(
+            "2026-01-01 00:00:30".parse::<Timestamp>(),
+            "2026-01-01T00:00:30".parse::<Timestamp>()
+        );
+    }
+
+    #[test]
+    fn test_timestamp_bad_suffixes() {
+        for timestamp in ["2y", "2 d", "2dd", "d", "", "2026-13-01", "yesterday"] {
+            assert!(timestamp.parse::<Timestamp>().is_err(), "{timestamp}");
+        }
+    }
+
+    #[test]
+    fn test_timestamp_overflow() {
+        assert!("18446744073709551615w".parse::<Timestamp>().is_err());
+        assert!("99999999999999999999999d".parse::<Timestamp>().is_err());
+    }
+
+    #[test]
+    fn test_file_size_units() {
+        for (size, bytes) in [
+            ("512", 512),
+            ("512b", 512),
+            ("1.5K", 1_500),
+            ("2kb", 2_000),
+            ("1KiB", 1_024),
+            ("10M", 10_000_000),
+            ("10 MB", 10_000_000),
+            ("10MiB", 10_485_760),
+            ("1G", 1_000_000_000),
+            ("1gib", 1_073_741_824),
+            ("2T", 2_000_000_000_000),
+        ] {
+            assert_eq!(size.parse::<FileSize>(), Ok(FileSize(bytes)), "{size}");
+        }
+    }
+
+    #[test]
+    fn test_file_size_bad_suffixes() {
+        for size in ["10x", "10TiB", "M", "", "1.2.3K", "-1K"] {
+            assert!(size.parse::<FileSize>().is_err(), "{size}");
+        }
+    }
+
+    #[test]
+    fn test_file_size_overflow() {
+        assert!("99999999999999999999999T".parse::<FileSize>().is_err());
+        assert!("18446744073709551616".parse::<FileSize>().is_err());
+    }
+
+    #[test]
+    fn test_size_range_bounds() {
+        assert_eq!(
+            "1K..5M".parse::<SizeRange>(),
+            Ok(SizeRange {
+                min: Some(1_000),
+                max: Some(5_000_000)
+            })
+        );
+        assert_eq!(
+            "1K..1K".parse::<SizeRange>(),
+            Ok(SizeRange {
+                min: Some(1_000),
+                max: Some(1_000)
+            })
+        );
+    }
+
+    #[test]
+    fn test_size_range_open_ended() {
+        assert_eq!(
+            "..5M".parse::<SizeRange>(),
+            Ok(SizeRange {
+                min: None,
+                max: Some(5_000_000)
+            })
+        );
+        assert_eq!(
+            "1K..".parse::<SizeRange>(),
+            Ok(SizeRange {
+                min: Some(1_000),
+                max: None
+            })
+        );
+        assert!("..".parse::<SizeRange>().is_err());
+    }
+
+    #[test]
+    fn test_size_range_invalid() {
+        assert!("5M..1K".parse::<SizeRange>().is_err());
+        assert!("1K".parse::<SizeRange>().is_err());
+        assert!("1K..5x".parse::<SizeRange>().is_err());
+        assert!("..99999999999999999999999T".parse::<SizeRange>().is_err());
+    }
+}
@@ -3,10 +3,11 @@
 use crate::{
     cli::global::GlobalArgs,
     git::utils::paint_git_item,
+    models::LineMatch,
     style::models::NomadStyle,
     utils::{
-        meta::get_metadata,
-        paths::{get_filename, get_symlink},
+        meta::{convert_bytes, convert_relative_time, convert_time, get_metadata},
+        paths::{get_filename, get_last_components, get_symlink},
     },
 };
 
@@ -14,25 +15,53 @@ use ansi_term::Colour;
 
 use std::{ffi::OsStr, path::Path};
 
-use super::models::TransformedBranch;
+use super::{
+    models::{
+        ItemChurn, ItemOwners, ItemSize, LastCommit, LineChanges, RevisionMetadata,
+        TransformedBranch,
+    },
+    utils::{format_item_size, get_percentage},
+};
+
+/// A gradient of the blues, greens, yellows, and reds in `XTERM_COLORS`, from the
+/// coolest to the hottest. Used to color items by their churn.
+const HEAT_COLORS: [u8; 21] = [
+    021, 027, 033, 039, 045, 051, 050, 049, 048, 047, 046, 082, 118, 154, 190, 226, 220, 214, 208,
+    202, 196,
+];
 
 /// Format how directories are displayed in the tree.
+///
+/// The directory's total size is displayed after its name if it is provided. The
+/// name includes the last `name_components` components of the directory's path.
 pub fn format_directory(
     args: &GlobalArgs,
+    churn: Option<&ItemChurn>,
     item: &Path,
     label: Option<String>,
+    last_commit: Option<&LastCommit>,
+    line_changes: Option<LineChanges>,
     matched: Option<(usize, usize)>,
+    name_components: usize,
     nomad_style: &NomadStyle,
+    owners: Option<&ItemOwners>,
+    revision_metadata: Option<&RevisionMetadata>,
+    size: Option<ItemSize>,
     target_directory: &str,
 ) -> String {
     let icon = "\u{f115}".to_string(); // 
-    let metadata = get_metadata(args, item);
+    let metadata = match revision_metadata {
+        Some(revision_metadata) => format_revision_metadata(args, revision_metadata),
+        None => get_metadata(args, item),
+    };
+
+    let directory_name = get_last_components(item.to_str().unwrap_or("?"), name_components);
 
     let mut directory_label = if args.style.plain || args.style.no_colors {
-        get_filename(item)
+        directory_name
     } else {
         match matched {
-            Some(ranges) => highlight_matched(
+            Some(ranges) => highlight_matched_components(
                 true,
                 nomad_style,
                 item.strip_prefix(target_directory)
@@ -41,13 +70,20 @@ pub fn format_directory(
                     .unwrap_or("?")
                     .to_string(),
                 ranges,
+                name_components,
             ),
-            None => nomad_style
-                .tree
-                .item_colors
-                .directory_color
-                .paint(get_filename(item))
-                .to_string(),
+            None => match churn {
+                Some(churn) => get_heat_color(churn.heat)
+                    .bold()
+                    .paint(directory_name)
+                    .to_string(),
+                None => nomad_style
+                    .tree
+                    .item_colors
+                    .directory_color
+                    .paint(directory_name)
+                    .to_string(),
+            },
         }
     };
 
@@ -64,6 +100,28 @@ pub fn format_directory(
         );
     }
 
+    if let Some(size) = size {
+        directory_label = format!("{directory_label} [{}]", format_item_size(args, &size));
+    }
+    if let Some(churn) = churn {
+        directory_label = format!("{directory_label} {}", format_churn(args, churn));
+    }
+    if let Some(line_changes) = line_changes {
+        directory_label = format!(
+            "{directory_label} {}",
+            format_line_changes(args, line_changes)
+        );
+    }
+    if let Some(last_commit) = last_commit {
+        directory_label = format!(
+            "{directory_label} {}",
+            format_last_commit(args, last_commit)
+        );
+    }
+    if let Some(owners) = owners {
+        directory_label = format!("{directory_label} {}", format_owners(args, owners));
+    }
+
     let mut formatted = if args.style.no_icons || args.style.plain {
         directory_label
     } else {
@@ -87,16 +145,24 @@ pub fn format_directory(
 /// Format how directory contents are displayed in the tree.
 pub fn format_content(
     args: &GlobalArgs,
+    churn: Option<&ItemChurn>,
     git_marker: Option<String>,
     icon: String,
     item: &Path,
+    last_commit: Option<&LastCommit>,
+    line_changes: Option<LineChanges>,
     matched: Option<(usize, usize)>,
     nomad_style: &NomadStyle,
     number: Option<i32>,
+    owners: Option<&ItemOwners>,
+    revision_metadata: Option<&RevisionMetadata>,
     target_directory: &str,
 ) -> String {
     let mut filename = get_filename(item);
-    let metadata = get_metadata(args, item);
+    let metadata = match revision_metadata {
+        Some(revision_metadata) => format_revision_metadata(args, revision_metadata),
+        None => get_metadata(args, item),
+    };
 
     let mut item_string =
         if let (Some(marker), false) = (git_marker, args.style.no_git || args.style.plain) {
@@ -139,6 +205,8 @@ pub fn format_content(
                         .to_string(),
                     ranges,
                 )
+            } else if let Some(churn) = churn {
+                get_heat_color(churn.heat).paint(filename).to_string()
             } else {
                 filename
             };
@@ -159,6 +227,19 @@ pub fn format_content(
         );
     }
 
+    if let Some(churn) = churn {
+        item_string = format!("{item_string} {}", format_churn(args, churn));
+    }
+    if let Some(line_changes) = line_changes {
+        item_string = format!("{item_string} {}", format_line_changes(args, line_changes));
+    }
+    if let Some(last_commit) = last_commit {
+        item_string = format!("{item_string} {}", format_last_commit(args, last_commit));
+    }
+    if let Some(owners) = owners {
+        item_string = format!("{item_string} {}", format_owners(args, owners));
+    }
+
     if let Some(number) = number {
         item_string = format!(
             "[{}] {item_string}",
@@ -176,12 +257,237 @@ pub fn format_content(
     item_string
 }
 
+/// Format the number of commits that touched an item, ie. `12 commits`, in the item's
+/// heat color.
+pub fn format_churn(args: &GlobalArgs, churn: &ItemChurn) -> String {
+    let commits = format!(
+        "{} commit{plurality}",
+        churn.commits,
+        plurality = if churn.commits == 1 { "" } else { "s" }
+    );
+
+    if args.style.plain || args.style.no_colors {
+        commits
+    } else {
+        get_heat_color(churn.heat).paint(commits).to_string()
+    }
+}
+
+/// Get the color within `HEAT_COLORS` for a heat from 0.0 to 1.0.
+fn get_heat_color(heat: f64) -> Colour {
+    let index = (heat.clamp(0.0, 1.0) * (HEAT_COLORS.len() - 1) as f64).round() as usize;
+
+    Colour::Fixed(HEAT_COLORS[index])
+}
+
+/// Format the number of lines that were added and deleted, ie. `+12 -3`.
+pub fn format_line_changes(args: &GlobalArgs, line_changes: LineChanges) -> String {
+    let added = format!("+{}", line_changes.added);
+    let deleted = format!("-{}", line_changes.deleted);
+
+    if args.style.plain || args.style.no_colors {
+        format!("{added} {deleted}")
+    } else {
+        format!(
+            "{} {}",
+            Colour::Green.paint(added),
+            Colour::Red.paint(deleted)
+        )
+    }
+}
+
+/// Format the commit that last changed an item, ie.
+/// `1a2b3c4 Alice 3 days ago Fix the parser`.
+pub fn format_last_commit(args: &GlobalArgs, last_commit: &LastCommit) -> String {
+    let time = convert_relative_time(last_commit.time);
+
+    if args.style.plain || args.style.no_colors {
+        format!(
+            "{} {} {time} {}",
+            last_commit.id, last_commit.author, last_commit.summary
+        )
+    } else {
+        format!(
+            "{} {} {} {}",
+            Colour::Fixed(193).paint(&last_commit.id),
+            Colour::Fixed(194).paint(&last_commit.author),
+            Colour::Fixed(035).paint(time),
+            Colour::Fixed(246).paint(&last_commit.summary)
+        )
+    }
+}
+
+/// Format the owners of an item and the percentage of lines each of them owns, ie.
+/// `Alice 62%, Bob 38%`.
+pub fn format_owners(args: &GlobalArgs, item_owners: &ItemOwners) -> String {
+    item_owners
+        .owners
+        .iter()
+        .map(|(name, lines)| {
+            let percentage = format!("{:.0}%", get_percentage(*lines, item_owners.total_lines));
+
+            if args.style.plain || args.style.no_colors {
+                format!("{name} {percentage}")
+            } else {
+                format!(
+                    "{} {}",
+                    Colour::Fixed(194).paint(name),
+                    Colour::Fixed(035).paint(percentage)
+                )
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Format the metadata of an item at a Git revision, ie. the size of its blob
+/// followed by the short SHA, author, and time of the commit that last changed it.
+pub fn format_revision_metadata(args: &GlobalArgs, revision_metadata: &RevisionMetadata) -> String {
+    let plain_size = convert_bytes(i64::try_from(revision_metadata.size).unwrap_or(i64::MAX));
+
+    let plain_commit = revision_metadata.last_commit.as_ref().map(|last_commit| {
+        (
+            last_commit.id.to_string(),
+            last_commit.author.to_string(),
+            convert_time(last_commit.time),
+        )
+    });
+
+    if args.style.plain || args.style.no_colors {
+        match plain_commit {
+            Some((id, author, time)) => format!("{plain_size} {id} {author} {time}"),
+            None => plain_size,
+        }
+    } else {
+        let size = Colour::Fixed(172).paint(plain_size);
+
+        match plain_commit {
+            Some((id, author, time)) => format!(
+                "{size} {} {} {}",
+                Colour::Fixed(193).paint(id),
+                Colour::Fixed(194).paint(author),
+                Colour::Fixed(035).paint(time)
+            ),
+            None => size.to_string(),
+        }
+    }
+}
+
+/// Format a line that matched the content search, ie. `12:5  let matched = true;`.
+///
+/// Leading and trailing whitespace is trimmed from the line and every match within
+/// it is highlighted.
+pub fn format_line_match(
+    args: &GlobalArgs,
+    line_match: &LineMatch,
+    nomad_style: &NomadStyle,
+) -> String {
+    let position = format!("{}:{}", line_match.line_number, line_match.column);
+
+    let trimmed = line_match.line.trim_end();
+    let offset = trimmed.len() - trimmed.trim_start().len();
+
+    if args.style.plain || args.style.no_colors {
+        return format!("{position}  {}", &trimmed[offset..]);
+    }
+
+    let mut highlighted = String::new();
+    let mut last_end = offset;
+    for span in line_match.matches.iter() {
+        let (start, end) = (span.start.max(last_end), span.end.min(trimmed.len()));
+        if start >= end {
+            continue;
+        }
+
+        highlighted.push_str(&trimmed[last_end..start]);
+        highlighted.push_str(
+            &nomad_style
+                .tree
+                .regex
+                .match_color
+                .paint(&trimmed[start..end])
+                .to_string(),
+        );
+
+        last_end = end;
+    }
+    highlighted.push_str(&trimmed[last_end..]);
+
+    format!("{}  {highlighted}", Colour::Fixed(243).paint(position))
+}
+
+/// Format the item that replaces the items hidden by the item limit, ie.
+/// `… 1,234 more (56 dirs, 1,178 files)`.
+pub fn format_hidden_summary(
+    args: &GlobalArgs,
+    num_directories: usize,
+    num_files: usize,
+) -> String {
+    let mut counts = Vec::new();
+    if num_directories > 0 {
+        counts.push(format!(
+            "{} {}",
+            format_count(num_directories),
+            if num_directories == 1 { "dir" } else { "dirs" }
+        ));
+    }
+    if num_files > 0 {
+        counts.push(format!(
+            "{} {}",
+            format_count(num_files),
+            if num_files == 1 { "file" } else { "files" }
+        ));
+    }
+
+    let summary = format!(
+        "… {} more ({})",
+        format_count(num_directories + num_files),
+        counts.join(", ")
+    );
+
+    if args.style.plain || args.style.no_colors {
+        summary
+    } else {
+        Colour::White.dimmed().italic().paint(summary).to_string()
+    }
+}
+
+/// Format a count with commas separating every three digits, ie. `1,234`.
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+
+    digits
+        .chars()
+        .enumerate()
+        .fold(String::new(), |mut formatted, (index, digit)| {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                formatted.push(',');
+            }
+            formatted.push(digit);
+
+            formatted
+        })
+}
+
 /// Reformat the filename if a pattern was provided and matched.
 pub fn highlight_matched(
     for_dir: bool,
     nomad_style: &NomadStyle,
     path: String,
     ranges: (usize, usize),
+) -> String {
+    highlight_matched_components(for_dir, nomad_style, path, ranges, 1)
+}
+
+/// Reformat the last `num_components` components of the path if a pattern was
+/// provided and matched. The match is highlighted within the whole path, so the
+/// highlight lines up with names that are made of multiple components.
+pub fn highlight_matched_components(
+    for_dir: bool,
+    nomad_style: &NomadStyle,
+    path: String,
+    ranges: (usize, usize),
+    num_components: usize,
 ) -> String {
     if (0..path.len()).contains(&ranges.0) && (0..path.len() + 1).contains(&ranges.1) {
         let mut prefix = path[..ranges.0]
@@ -234,19 +540,9 @@ pub fn highlight_matched(
 
         let highlighted_path = prefix.join("");
 
-        Path::new(&highlighted_path)
-            .file_name()
-            .unwrap_or_else(|| OsStr::new("?"))
-            .to_str()
-            .unwrap_or("?")
-            .to_string()
+        get_last_components(&highlighted_path, num_components)
     } else {
-        Path::new(&path)
-            .file_name()
-            .unwrap_or_else(|| OsStr::new("?"))
-            .to_str()
-            .unwrap_or("?")
-            .to_string()
+        get_last_components(&path, num_components)
     }
 }
 
@@ -290,6 +586,35 @@ pub fn format_branch(
     if let Some(upstream) = &item.upstream {
         branch_name.push_str(upstream);
     }
+    branch_name.push_str(&format_branch_details(
+        item.ahead_behind,
+        item.last_commit_time,
+    ));
 
     branch_name
 }
+
+/// Format how far a branch is ahead of and behind its upstream branch and when its
+/// last commit was made, ie. ` ↑2 ↓1 3 days ago`.
+pub fn format_branch_details(
+    ahead_behind: Option<(usize, usize)>,
+    last_commit_time: Option<i64>,
+) -> String {
+    let mut details = String::new();
+
+    if let Some((ahead, behind)) = ahead_behind {
+        details.push_str(&format!(
+            " {} {}",
+            Colour::Green.bold().paint(format!("↑{ahead}")),
+            Colour::Red.bold().paint(format!("↓{behind}"))
+        ));
+    }
+    if let Some(last_commit_time) = last_commit_time {
+        details.push_str(&format!(
+            " {}",
+            Colour::Fixed(035).paint(convert_relative_time(last_commit_time))
+        ));
+    }
+
+    details
+}
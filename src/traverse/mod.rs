@@ -1,5 +1,6 @@
 //! Traverse the target directory.
 
+pub mod filters;
 pub mod format;
 pub mod models;
 pub mod modes;
@@ -7,30 +8,171 @@ pub mod traits;
 pub mod utils;
 
 use self::{
-    models::{DirItem, FoundItem},
-    modes::NomadMode,
-    traits::{ToTree, TransformFound},
+    models::{DirItem, FoundItem, ItemSize, TransformedItem},
+    modes::{NomadMode, SortMode},
+    traits::{StreamTree, ToJson, ToTree, TransformFound},
+    utils::{
+        build_walk_builder, build_walker, collect_entries, display_heaviest_directories,
+        get_disk_usage, get_sort_options, prune_directories, search_file_contents, sort_items,
+    },
 };
 use crate::{
-    cli::global::GlobalArgs, errors::NomadError, git::markers::extend_marker_map,
-    style::models::NomadStyle, utils::paths::canonicalize_path,
+    cli::global::GlobalArgs,
+    errors::NomadError,
+    git::{
+        markers::{
+            extend_status_map, extend_status_map_at_root, get_status_marker, matches_status_filters,
+        },
+        revision::set_last_commits,
+    },
+    models::JsonItem,
+    style::models::NomadStyle,
+    utils::paths::canonicalize_path,
 };
 
 use anyhow::{Result, __private};
-use ignore::{self, Walk};
+use ignore::{self, types::Types, WalkBuilder};
 use ptree::{item::StringItem, PrintConfig};
 use regex::Regex;
 
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 /// Traverse the directory and display files and directories accordingly.
+///
+/// A JSON tree is also returned if the tree is exported to a format that is built
+/// from it.
 pub fn walk_directory(
     args: &GlobalArgs,
     nomad_mode: NomadMode,
     nomad_style: &NomadStyle,
     target_directory: &str,
-    walker: &mut Walk,
-) -> Result<(StringItem, PrintConfig, Option<Vec<DirItem>>), NomadError> {
+    walk_builder: &mut WalkBuilder,
+) -> Result<
+    (
+        StringItem,
+        PrintConfig,
+        Option<Vec<DirItem>>,
+        Option<JsonItem>,
+    ),
+    NomadError,
+> {
+    let (transformed_items, disk_usage, num_pruned) =
+        get_transformed_items(args, nomad_style, target_directory, walk_builder)?;
+
+    let json_item = match (&args.export, args.export_format) {
+        (Some(_), export_format) if export_format.is_structured() => {
+            Some(transformed_items.to_json(args, target_directory)?)
+        }
+        _ => None,
+    };
+
+    let is_normal_mode = matches!(nomad_mode, NomadMode::Normal);
+    let (tree, config, directory_items) =
+        transformed_items.to_tree(args, nomad_mode, nomad_style, target_directory)?;
+
+    if let (true, Some(disk_usage)) = (
+        is_normal_mode && args.statistics && args.modifiers.du,
+        disk_usage,
+    ) {
+        display_heaviest_directories(args, &disk_usage, target_directory);
+    }
+    if is_normal_mode && args.statistics && args.modifiers.prune {
+        println!("| {num_pruned} empty directories pruned |\n");
+    }
+
+    Ok((tree, config, directory_items, json_item))
+}
+
+/// Traverse the directory and display files and directories as a JSON document.
+pub fn json_directory(
+    args: &GlobalArgs,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+    types: Option<Types>,
+) -> Result<(), NomadError> {
+    let (transformed_items, _, _) = get_transformed_items(
+        args,
+        nomad_style,
+        target_directory,
+        &mut build_walk_builder(args, target_directory, types)?,
+    )?;
+    let json_item = transformed_items.to_json(args, target_directory)?;
+
+    println!("{}", serde_json::to_string_pretty(&json_item)?);
+
+    Ok(())
+}
+
+/// Walk the directory and transform the files that were found for tree building.
+///
+/// Item sizes are set, empty directories are pruned, last commits are looked up, and
+/// items are sorted here. The size of every item that was walked is also returned if
+/// sizes were needed, along with the number of pruned directories.
+fn get_transformed_items(
+    args: &GlobalArgs,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+    walk_builder: &mut WalkBuilder,
+) -> Result<
+    (
+        Vec<TransformedItem>,
+        Option<HashMap<PathBuf, ItemSize>>,
+        usize,
+    ),
+    NomadError,
+> {
+    let sort_options = get_sort_options(args, nomad_style);
+
+    let (found_items, disk_usage) = find_items(
+        args,
+        nomad_style,
+        target_directory,
+        walk_builder,
+        args.modifiers.du || sort_options.sort_mode == SortMode::Size,
+    )?;
+    let mut transformed_items = found_items.transform(target_directory)?;
+
+    if let Some(ref disk_usage) = disk_usage {
+        for item in transformed_items.iter_mut() {
+            item.size = disk_usage.get(Path::new(&item.path)).copied();
+        }
+    }
+
+    let mut num_pruned = 0;
+    if args.modifiers.prune {
+        (transformed_items, num_pruned) = prune_directories(transformed_items);
+
+        if transformed_items.is_empty() {
+            return Err(NomadError::NothingFound);
+        }
+    }
+
+    if args.meta.last_commit {
+        set_last_commits(&mut transformed_items, target_directory)?;
+    }
+
+    Ok((
+        sort_items(transformed_items, &sort_options, target_directory),
+        disk_usage,
+        num_pruned,
+    ))
+}
+
+/// Walk the directory and collect the files that should be displayed in the tree.
+///
+/// If sizes are counted, the directory is walked past the maximum depth so that
+/// directory sizes include everything within them. Items that are too deep are
+/// still left out of the tree.
+fn find_items(
+    args: &GlobalArgs,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+    walk_builder: &mut WalkBuilder,
+    count_sizes: bool,
+) -> Result<(Vec<FoundItem>, Option<HashMap<PathBuf, ItemSize>>), NomadError> {
     let regex_expression = if let Some(ref pattern) = args.regex.pattern {
         match Regex::new(&pattern.clone()) {
             Ok(regex) => Some(regex),
@@ -39,67 +181,120 @@ pub fn walk_directory(
     } else {
         None
     };
+    let grep_expression = if let Some(ref grep) = args.regex.grep {
+        match Regex::new(grep) {
+            Ok(regex) => Some(regex),
+            Err(error) => return __private::Err(NomadError::RegexError(error)),
+        }
+    } else {
+        None
+    };
 
-    let mut git_markers: HashMap<String, String> = HashMap::new();
-    extend_marker_map(
-        &args.style,
-        &mut git_markers,
-        nomad_style,
+    let mut git_statuses = HashMap::new();
+    extend_status_map(
+        &mut git_statuses,
+        args.modifiers.show_ignored,
         Path::new(target_directory).to_str().unwrap_or("?"),
     );
 
-    let (tree, config, directory_items) = walker
-        .filter_map(|dir_entry| {
-            if let Ok(entry) = dir_entry {
-                if entry.path().is_dir() {
-                    extend_marker_map(
-                        &args.style,
-                        &mut git_markers,
-                        nomad_style,
-                        entry.path().to_str().unwrap_or("?"),
-                    );
-                    None
-                } else if let Some(ref regex) = regex_expression {
-                    if let Some(matched) = regex.find(
+    if count_sizes {
+        walk_builder.max_depth(None);
+    }
+
+    let entries = collect_entries(
+        walk_builder,
+        args.modifiers.threads.or(nomad_style.tree.threads),
+    );
+    let disk_usage = if count_sizes {
+        Some(get_disk_usage(&entries))
+    } else {
+        None
+    };
+
+    let found_items = entries
+        .into_iter()
+        .filter_map(|entry| {
+            if let Some(max_depth) = args.modifiers.max_depth {
+                if entry.depth() > max_depth {
+                    return None;
+                }
+            }
+
+            if entry.path().is_dir() {
+                extend_status_map_at_root(
+                    &mut git_statuses,
+                    args.modifiers.show_ignored,
+                    entry.path().to_str().unwrap_or("?"),
+                );
+
+                // Directories are normally displayed through the files within them. Keep
+                // directories at the maximum depth so that their sizes are displayed.
+                if !count_sizes
+                    || entry.depth() == 0
+                    || args.modifiers.max_depth != Some(entry.depth())
+                {
+                    return None;
+                }
+            }
+
+            let matched = match regex_expression {
+                Some(ref regex) => Some(
+                    regex.find(
                         entry
                             .path()
                             .strip_prefix(target_directory)
                             .unwrap_or_else(|_| Path::new("?"))
                             .to_str()
                             .unwrap_or("?"),
-                    ) {
-                        Some(FoundItem {
-                            marker: git_markers
-                                .get(
-                                    &canonicalize_path(entry.path().to_str().unwrap_or("?"))
-                                        .unwrap_or_else(|_| "?".to_string()),
-                                )
-                                .map(|marker| marker.to_string()),
-                            matched: Some((matched.start(), matched.end())),
-                            path: entry.path().to_str().unwrap_or("?").to_string(),
-                        })
-                    } else {
-                        None
-                    }
-                } else {
-                    Some(FoundItem {
-                        marker: git_markers
-                            .get(
-                                &canonicalize_path(entry.path().to_str().unwrap_or("?"))
-                                    .unwrap_or_else(|_| "?".to_string()),
-                            )
-                            .map(|marker| marker.to_string()),
-                        matched: None,
-                        path: entry.path().to_str().unwrap_or("?").to_string(),
-                    })
+                    )?,
+                ),
+                None => None,
+            };
+
+            // Directories at the maximum depth are kept regardless of their contents.
+            let line_matches = match grep_expression {
+                Some(ref regex) if !entry.path().is_dir() => {
+                    search_file_contents(entry.path(), regex)?
                 }
-            } else {
-                None
+                _ => Vec::new(),
+            };
+
+            let status = git_statuses
+                .get(
+                    &canonicalize_path(entry.path().to_str().unwrap_or("?"))
+                        .unwrap_or_else(|_| "?".to_string()),
+                )
+                .copied();
+            if !entry.path().is_dir() && !matches_status_filters(&args.modifiers.only, status) {
+                return None;
             }
+
+            Some(FoundItem {
+                line_matches,
+                marker: status.map(|status| get_status_marker(&args.style, nomad_style, status)),
+                matched: matched.map(|matched| (matched.start(), matched.end())),
+                path: entry.path().to_str().unwrap_or("?").to_string(),
+                status,
+            })
         })
-        .collect::<Vec<FoundItem>>()
-        .transform(target_directory)?
-        .to_tree(args, nomad_mode, nomad_style, target_directory)?;
+        .collect::<Vec<FoundItem>>();
+
+    Ok((found_items, disk_usage))
+}
 
-    Ok((tree, config, directory_items))
+/// Traverse the directory and display files and directories as soon as they are visited.
+///
+/// The tree is streamed directly to `stdout`, so nothing is returned for exporting.
+pub fn stream_directory(
+    args: &GlobalArgs,
+    nomad_style: &NomadStyle,
+    target_directory: &str,
+    types: Option<Types>,
+) -> Result<(), NomadError> {
+    build_walker(args, target_directory, types.clone())?.stream_tree(
+        args,
+        nomad_style,
+        target_directory,
+        types,
+    )
 }
@@ -1,16 +1,28 @@
 //! Structs used during directory traversal.
 
+use super::modes::SortMode;
+use crate::models::LineMatch;
+
+use git2::Status;
+
+use std::{collections::VecDeque, path::PathBuf};
+
 /// Contains the path of the found item and its corresponding Git marker if applicable.
 ///
 /// This struct is used to convert `DirEntry`s returned by the `Walk` object.
 #[derive(Debug)]
 pub struct FoundItem {
+    /// The lines in the file that matched the content search. This is empty unless
+    /// `--grep` was used.
+    pub line_matches: Vec<LineMatch>,
     /// The Git status marker indicating the change that was made to the file.
     pub marker: Option<String>,
     /// The start and end of the pattern match in the path.
     pub matched: Option<(usize, usize)>,
     /// The filepath.
     pub path: String,
+    /// The Git status bits of the file if it contains changes.
+    pub status: Option<Status>,
 }
 
 /// Contains metadata for each path.
@@ -18,6 +30,9 @@ pub struct FoundItem {
 /// The `TransformFound` trait converts a `FoundItem` into this struct for tree building.
 #[derive(Debug)]
 pub struct TransformedItem {
+    /// The number of commits that touched the item. This is only set for `git churn`
+    /// trees.
+    pub churn: Option<ItemChurn>,
     /// The filepath broken down into its individual components.
     pub components: Vec<String>,
     /// The depth of the file relative to the root of the directory.
@@ -26,12 +41,110 @@ pub struct TransformedItem {
     pub is_dir: bool,
     /// Indicates whether this is a file.
     pub is_file: bool,
+    /// The commit that last changed the item. This is only set if `--last-commit`
+    /// was passed.
+    pub last_commit: Option<LastCommit>,
+    /// The number of lines that were added and deleted in the file, or in every file
+    /// within the directory. This is only set for trees of Git changes.
+    pub line_changes: Option<LineChanges>,
+    /// The lines in the file that matched the content search.
+    pub line_matches: Vec<LineMatch>,
     /// The Git status marker indicating the change that was made to the file.
     pub marker: Option<String>,
     /// The start and end of the pattern match in the path.
     pub matched: Option<(usize, usize)>,
+    /// The number of path components displayed as the item's name. This is greater
+    /// than 1 if directories were collapsed into this directory.
+    pub name_components: usize,
+    /// The authors that own the most lines in the file, or in every file within the
+    /// directory. This is only set for `git owners` trees.
+    pub owners: Option<ItemOwners>,
     /// The absolute filepath.
     pub path: String,
+    /// The item's metadata at a Git revision. This is only set if the tree was built
+    /// from a revision instead of the filesystem.
+    pub revision_metadata: Option<RevisionMetadata>,
+    /// The size of the file, or the total size of the directory's contents. This is
+    /// only set if directory sizes were requested.
+    pub size: Option<ItemSize>,
+    /// The Git status bits of the file if it contains changes.
+    pub status: Option<Status>,
+}
+
+/// Contains the apparent and on-disk sizes of an item in bytes.
+///
+/// Directory sizes are the totals of every item within the directory, including
+/// items that are deeper than the maximum depth.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ItemSize {
+    /// The number of bytes the item contains.
+    pub apparent: u64,
+    /// The number of bytes the item takes up on disk.
+    pub disk: u64,
+}
+
+/// Contains the number of lines that were added and deleted in a Git diff.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LineChanges {
+    /// The number of added lines.
+    pub added: usize,
+    /// The number of deleted lines.
+    pub deleted: usize,
+}
+
+/// Contains an item's metadata at a Git revision.
+#[derive(Clone, Debug)]
+pub struct RevisionMetadata {
+    /// The commit that last changed the item. This is only set if metadata was
+    /// requested.
+    pub last_commit: Option<LastCommit>,
+    /// The size of the file's blob, or the total size of the blobs within the
+    /// directory.
+    pub size: u64,
+}
+
+/// Contains the commit that last changed an item.
+#[derive(Clone, Debug)]
+pub struct LastCommit {
+    /// The name of the commit's author.
+    pub author: String,
+    /// The abbreviated SHA of the commit.
+    pub id: String,
+    /// The first line of the commit message.
+    pub summary: String,
+    /// The time the commit was authored as a UNIX timestamp.
+    pub time: i64,
+}
+
+/// Contains the number of commits that touched a file, or any file within a directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ItemChurn {
+    /// The number of commits.
+    pub commits: usize,
+    /// The number of commits relative to the other files or directories, from 0.0 for
+    /// the least touched to 1.0 for the most touched.
+    pub heat: f64,
+}
+
+/// Contains the owners of the lines in a file, or in every file within a directory.
+#[derive(Clone, Debug, Default)]
+pub struct ItemOwners {
+    /// The owners with the most lines and the number of lines each of them owns, most
+    /// lines first.
+    pub owners: Vec<(String, usize)>,
+    /// The total number of lines, including lines owned by owners that were left out.
+    pub total_lines: usize,
+}
+
+/// Contains the settings used to sort the items within each directory.
+#[derive(Debug)]
+pub struct SortOptions {
+    /// Display directories before files.
+    pub dirs_first: bool,
+    /// Reverse the sort order.
+    pub reverse: bool,
+    /// The value items are sorted by.
+    pub sort_mode: SortMode,
 }
 
 /// Contains metadata for `git branch` items.
@@ -40,12 +153,16 @@ pub struct TransformedItem {
 /// building.
 #[derive(Debug)]
 pub struct FoundBranch {
+    /// The number of commits the branch is ahead of and behind its upstream branch.
+    pub ahead_behind: Option<(usize, usize)>,
     /// The full branch name.
     pub full_branch: String,
     /// Indicates whether this is the current branch.
     pub is_current_branch: bool,
     /// Indicates whether this branch points to `HEAD`.
     pub is_head: bool,
+    /// The UNIX timestamp of the last commit on the branch.
+    pub last_commit_time: Option<i64>,
     /// The marker indicating whether this is the current branch.
     pub marker: Option<String>,
     /// The start and end of the pattern match in the branch name.
@@ -60,8 +177,8 @@ pub struct FoundBranch {
 /// `feature/something_new`.
 #[derive(Debug)]
 pub struct TransformedBranch {
-    /// The branch name broken down into its individual components.
-    pub components: Vec<String>,
+    /// The number of commits the branch is ahead of and behind its upstream branch.
+    pub ahead_behind: Option<(usize, usize)>,
     /// The depth of the branch relative to its components.
     pub depth: i32,
     /// The full branch name.
@@ -75,6 +192,8 @@ pub struct TransformedBranch {
     /// Indicates whether the branch name has a parent name. For example, if the
     /// branch name is `feature/something_new`, the parent would be `feature`.
     pub is_parent: bool,
+    /// The UNIX timestamp of the last commit on the branch.
+    pub last_commit_time: Option<i64>,
     /// The marker indicating whether this is the current branch.
     pub marker: Option<String>,
     /// The start and end of the pattern match in the branch name.
@@ -94,3 +213,33 @@ pub struct DirItem {
     /// The absolute path to this item.
     pub path: String,
 }
+
+/// Contains the prefixes that are drawn in front of each item when the tree is streamed.
+///
+/// These mirror the prefixes `ptree` builds from the `PrintConfig` so that streamed
+/// trees look identical to buffered trees.
+#[derive(Debug)]
+pub struct TreePrefixes {
+    /// The prefix drawn in front of an item that has siblings below it.
+    pub regular: String,
+    /// The prefix drawn in front of the children of an item that has siblings below it.
+    pub child: String,
+    /// The prefix drawn in front of the last item in a directory.
+    pub last_regular: String,
+    /// The prefix drawn in front of the children of the last item in a directory.
+    pub last_child: String,
+}
+
+/// Contains a directory whose children are currently being streamed.
+///
+/// This struct is used when the tree is streamed to keep track of the items that
+/// still have to be displayed within each directory.
+#[derive(Debug)]
+pub struct OpenDirectory {
+    /// The prefix drawn in front of each child in this directory.
+    pub child_prefix: String,
+    /// The path to this directory.
+    pub path: PathBuf,
+    /// The items within this directory that have not been displayed yet.
+    pub remaining: VecDeque<PathBuf>,
+}
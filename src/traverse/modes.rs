@@ -1,5 +1,7 @@
 //! Traversal modes for `nomad`.
 
+use std::str::FromStr;
+
 /// Modes in which `nomad` may operate.
 pub enum NomadMode {
     /// Run `nomad` in `git branch` mode.
@@ -11,3 +13,50 @@ pub enum NomadMode {
     /// Run `nomad` in rootless (interactive) mode.
     Rootless,
 }
+
+/// Orders in which the items within each directory may be sorted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortMode {
+    /// Sort items by the number of commits that touched them, most first. This only
+    /// applies to `git churn` trees.
+    Churn,
+    /// Sort items by the time their metadata was last changed, newest first.
+    Ctime,
+    /// Sort items by their extensions. Items without an extension come first.
+    Extension,
+    /// Sort items by their Git status, starting with conflicts and staged changes.
+    /// Directories are sorted by the most important status within them.
+    GitStatus,
+    /// Sort items by lines of code, most first. Directories are sorted by the total
+    /// lines of code within them.
+    Loc,
+    /// Sort items by the time they were last modified, newest first.
+    Mtime,
+    /// Sort items by name. This is the order the directory is walked in.
+    Name,
+    /// Sort items by name, comparing numbers within names by their values.
+    Natural,
+    /// Sort items by size, largest first. Directories are sorted by the total size
+    /// of everything within them.
+    Size,
+}
+
+impl FromStr for SortMode {
+    type Err = String;
+
+    /// Convert the value passed into `--sort` into a `SortMode`.
+    fn from_str(sort_mode: &str) -> Result<Self, Self::Err> {
+        match sort_mode.to_lowercase().as_str() {
+            "churn" => Ok(SortMode::Churn),
+            "ctime" => Ok(SortMode::Ctime),
+            "extension" => Ok(SortMode::Extension),
+            "git-status" => Ok(SortMode::GitStatus),
+            "loc" => Ok(SortMode::Loc),
+            "mtime" => Ok(SortMode::Mtime),
+            "name" => Ok(SortMode::Name),
+            "natural" => Ok(SortMode::Natural),
+            "size" => Ok(SortMode::Size),
+            _ => Err(format!("{sort_mode} is not a supported sort mode")),
+        }
+    }
+}
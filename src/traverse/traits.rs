@@ -2,28 +2,43 @@
 
 use super::{
     format::format_branch,
-    models::{DirItem, FoundBranch, FoundItem, TransformedBranch, TransformedItem},
+    models::{DirItem, FoundBranch, FoundItem, OpenDirectory, TransformedBranch, TransformedItem},
     modes::NomadMode,
 };
 use crate::{
     cli::global::GlobalArgs,
     errors::NomadError,
-    loc::{loc_in_dir, loc_in_file},
+    git::markers::{
+        extend_status_map, extend_status_map_at_root, get_git_status, get_status_marker,
+    },
+    loc::{loc_in_dir, loc_in_file, loc_json_in_file},
+    models::{
+        JsonChurn, JsonItem, JsonItemType, JsonLastCommit, JsonMetadata, JsonOwner, MatchSpan,
+    },
     style::models::NomadStyle,
     traverse::{
-        format::{format_content, format_directory},
-        utils::{build_tree, check_nesting, get_file_icon, store_directory_contents},
+        format::{format_content, format_directory, format_hidden_summary, format_line_match},
+        utils::{
+            build_tree, build_tree_style, check_nesting, close_json_directories,
+            compact_directories, get_compacted_layout, get_directory_label, get_file_icon,
+            get_hidden_items, get_item_labels, get_percentage, get_stored_revision, get_tree_label,
+            get_tree_prefixes, list_children, store_branch_numbers, store_directory_contents,
+        },
     },
-    ALPHABET,
+    utils::{meta::get_json_metadata, paths::canonicalize_path},
 };
 
 use ansi_term::Colour;
-use ptree::{item::StringItem, print_tree_with, PrintConfig};
+use git2::Status;
+use ignore::{types::Types, Walk};
+use ptree::{item::StringItem, print_config::OutputKind, print_tree_with, PrintConfig};
+use regex::Regex;
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     ffi::OsStr,
-    path::{Component, Path},
+    io::{stdout, Write},
+    path::{Component, Path, PathBuf},
     time::Instant,
 };
 
@@ -60,33 +75,54 @@ impl TransformFound<TransformedItem> for Vec<FoundItem> {
                     if index < item.components().count() - 1 && !directories.contains(&joined_path)
                     {
                         transformed.push(TransformedItem {
+                            churn: None,
                             components: components.clone(),
                             depth,
                             is_dir: true,
                             is_file: false,
+                            last_commit: None,
+                            line_changes: None,
+                            line_matches: Vec::new(),
                             marker: None,
                             matched: found_item.matched,
+                            name_components: 1,
+                            owners: None,
                             path: Path::new(target_directory)
                                 .join(joined_path)
                                 .to_str()
                                 .unwrap_or("?")
                                 .to_string(),
+                            revision_metadata: None,
+                            size: None,
+                            status: None,
                         });
 
                         directories.insert(components.join("/").to_string());
                     } else if index == item.components().count() - 1 {
+                        // Directories are only found when they are at the maximum depth.
+                        let is_dir = Path::new(&found_item.path).is_dir();
+
                         transformed.push(TransformedItem {
+                            churn: None,
                             components: components.clone(),
                             depth,
-                            is_dir: false,
-                            is_file: true,
+                            is_dir,
+                            is_file: !is_dir,
+                            last_commit: None,
+                            line_changes: None,
+                            line_matches: found_item.line_matches.clone(),
                             marker: found_item.marker.clone(),
                             matched: found_item.matched,
+                            name_components: 1,
+                            owners: None,
                             path: Path::new(target_directory)
                                 .join(joined_path)
                                 .to_str()
                                 .unwrap_or("?")
                                 .to_string(),
+                            revision_metadata: None,
+                            size: None,
+                            status: found_item.status,
                         });
                     }
                 }
@@ -123,7 +159,7 @@ impl TransformFound<TransformedBranch> for Vec<FoundBranch> {
                         && !branch_parents.contains(&joined_branch_name)
                     {
                         transformed.push(TransformedBranch {
-                            components: components.clone(),
+                            ahead_behind: None,
                             depth,
                             full_branch: Path::new(&joined_branch_name)
                                 .to_str()
@@ -133,6 +169,7 @@ impl TransformFound<TransformedBranch> for Vec<FoundBranch> {
                             is_end: false,
                             is_head: found_branch.is_head,
                             is_parent: true,
+                            last_commit_time: None,
                             marker: None,
                             matched: None,
                             upstream: found_branch.upstream.clone(),
@@ -141,7 +178,7 @@ impl TransformFound<TransformedBranch> for Vec<FoundBranch> {
                         branch_parents.insert(components.join("/").to_string());
                     } else if index == item.components().count() - 1 {
                         transformed.push(TransformedBranch {
-                            components: components.clone(),
+                            ahead_behind: found_branch.ahead_behind,
                             depth,
                             full_branch: Path::new(&joined_branch_name)
                                 .to_str()
@@ -151,6 +188,7 @@ impl TransformFound<TransformedBranch> for Vec<FoundBranch> {
                             is_end: true,
                             is_head: found_branch.is_head,
                             is_parent: false,
+                            last_commit_time: found_branch.last_commit_time,
                             marker: found_branch.marker.clone(),
                             matched: found_branch.matched,
                             upstream: found_branch.upstream.clone(),
@@ -189,21 +227,12 @@ impl ToTree for Vec<TransformedItem> {
     ) -> Result<(StringItem, PrintConfig, Option<Vec<DirItem>>), NomadError> {
         let mut numbered_items: HashMap<String, String> = HashMap::new();
         let mut labeled_items: HashMap<String, String> = HashMap::new();
+        let mut match_positions: HashMap<String, (usize, usize)> = HashMap::new();
 
         let mut current_depth = 0;
-        let mut letter_index = 0; // The index pointing to a letter in the alphabet.
-        let mut loop_count = 0; // Count the number of times the alphabet has been looped.
         let mut num_directories = 0;
         let mut num_files = 0;
-        let mut previous_item = &TransformedItem {
-            components: vec![],
-            depth: 0,
-            is_dir: true,
-            is_file: false,
-            marker: None,
-            matched: None,
-            path: target_directory.to_string(),
-        };
+        let mut previous_is_dir = true; // The root of the tree is the first item.
 
         // This holds every single item in the directory and is only returned in
         // NomadMode::Rootless.
@@ -225,55 +254,100 @@ impl ToTree for Vec<TransformedItem> {
         let (config, mut tree) =
             build_tree(args, &nomad_mode, nomad_style, Path::new(target_directory));
 
+        let items = if args.modifiers.compact_dirs {
+            compact_directories(self)
+        } else {
+            self
+        };
+
+        // Rootless mode matches each line in the tree to an item, so every item is
+        // displayed in that mode.
+        let (hidden_items, hidden_summaries) = match (
+            &nomad_mode,
+            args.modifiers.max_items.or(nomad_style.tree.max_items),
+        ) {
+            (NomadMode::Rootless, _) | (_, None) => (vec![false; items.len()], HashMap::new()),
+            (_, Some(max_items)) => get_hidden_items(&items, max_items, args.modifiers.dirs),
+        };
+        let item_labels = get_item_labels(&items, args.modifiers.dirs);
+
         let start = Instant::now();
-        for item in self.iter() {
-            check_nesting(
-                current_depth,
-                Path::new(&target_directory)
-                    .join(Path::new(&item.components.join("/")))
-                    .as_path(),
-                &nomad_mode,
-                Path::new(&target_directory)
-                    .join(Path::new(&previous_item.components.join("/")))
-                    .as_path(),
-                target_directory,
-                &mut tree,
-            );
+        for (index, item) in items.iter().enumerate() {
+            if let Some((num_hidden_directories, num_hidden_files)) = hidden_summaries.get(&index) {
+                check_nesting(
+                    current_depth,
+                    item.depth as usize,
+                    previous_is_dir,
+                    &mut tree,
+                );
+
+                tree.add_empty_child(format_hidden_summary(
+                    args,
+                    *num_hidden_directories,
+                    *num_hidden_files,
+                ));
 
-            if item.is_dir {
-                if letter_index == 26 {
-                    loop_count += 1;
-                    letter_index = 0;
-                }
+                current_depth = item.depth as usize;
+                previous_is_dir = false;
+            }
 
-                let mut directory_label = ALPHABET.get(letter_index).unwrap_or(&'?').to_string();
+            // Hidden items are still labeled and numbered so that they may be used
+            // with subcommands.
+            if hidden_items[index] {
+                if let (true, Some(directory_label)) = (item.is_dir, &item_labels[index]) {
+                    labeled_items.insert(directory_label.to_string(), item.path.to_string());
+
+                    num_directories += 1;
+                } else if let Some(number) = &item_labels[index] {
+                    numbered_items.insert(number.to_string(), item.path.to_string());
+                    if let Some(line_match) = item.line_matches.first() {
+                        match_positions.insert(
+                            item.path.to_string(),
+                            (line_match.line_number, line_match.column),
+                        );
+                    }
 
-                if loop_count > 0 {
-                    directory_label.push_str(&loop_count.to_string());
+                    num_files += 1;
                 }
 
-                labeled_items.insert(directory_label.to_string(), item.path.to_string());
+                continue;
+            }
 
-                letter_index += 1;
+            check_nesting(
+                current_depth,
+                item.depth as usize,
+                previous_is_dir,
+                &mut tree,
+            );
+
+            if let (true, Some(directory_label)) = (item.is_dir, &item_labels[index]) {
+                labeled_items.insert(directory_label.to_string(), item.path.to_string());
 
                 let label = if args.labels.label_directories || args.labels.all_labels {
-                    Some(directory_label)
+                    Some(directory_label.to_string())
                 } else {
                     None
                 };
 
                 tree.begin_child(format_directory(
                     args,
+                    item.churn.as_ref(),
                     Path::new(&item.path),
                     label,
+                    item.last_commit.as_ref(),
+                    item.line_changes,
                     item.matched,
+                    item.name_components,
                     nomad_style,
+                    item.owners.as_ref(),
+                    item.revision_metadata.as_ref(),
+                    if args.modifiers.du { item.size } else { None },
                     target_directory,
                 ));
 
                 num_directories += 1;
-            } else if item.is_file && !args.modifiers.dirs {
-                numbered_items.insert(format!("{num_files}"), item.path.to_string());
+            } else if let Some(number) = &item_labels[index] {
+                numbered_items.insert(number.to_string(), item.path.to_string());
 
                 let number = if args.labels.numbers || args.labels.all_labels {
                     Some(num_files)
@@ -281,17 +355,29 @@ impl ToTree for Vec<TransformedItem> {
                     None
                 };
 
+                if let Some(line_match) = item.line_matches.first() {
+                    match_positions.insert(
+                        item.path.to_string(),
+                        (line_match.line_number, line_match.column),
+                    );
+                }
+
                 let icon = get_file_icon(Path::new(&item.path));
 
-                if args.meta.tokei {
+                if args.meta.tokei || !item.line_matches.is_empty() {
                     tree.begin_child(format_content(
                         args,
+                        item.churn.as_ref(),
                         item.marker.clone(),
                         icon,
                         Path::new(&item.path),
+                        item.last_commit.as_ref(),
+                        item.line_changes,
                         item.matched,
                         nomad_style,
                         number,
+                        item.owners.as_ref(),
+                        item.revision_metadata.as_ref(),
                         target_directory,
                     ));
 
@@ -299,18 +385,26 @@ impl ToTree for Vec<TransformedItem> {
                         for stat in loc_in_file(args, &item.path, tokei) {
                             tree.add_empty_child(stat);
                         }
-
-                        tree.end_child();
                     }
+                    for line_match in item.line_matches.iter() {
+                        tree.add_empty_child(format_line_match(args, line_match, nomad_style));
+                    }
+
+                    tree.end_child();
                 } else {
                     tree.add_empty_child(format_content(
                         args,
+                        item.churn.as_ref(),
                         item.marker.clone(),
                         icon,
                         Path::new(&item.path),
+                        item.last_commit.as_ref(),
+                        item.line_changes,
                         item.matched,
                         nomad_style,
                         number,
+                        item.owners.as_ref(),
+                        item.revision_metadata.as_ref(),
                         target_directory,
                     ));
                 }
@@ -319,7 +413,7 @@ impl ToTree for Vec<TransformedItem> {
             }
 
             current_depth = item.depth as usize;
-            previous_item = item;
+            previous_is_dir = item.is_dir;
 
             if let NomadMode::Rootless = nomad_mode {
                 directory_items.push(DirItem {
@@ -333,7 +427,12 @@ impl ToTree for Vec<TransformedItem> {
             }
         }
 
-        store_directory_contents(labeled_items, numbered_items)?;
+        store_directory_contents(
+            labeled_items,
+            numbered_items,
+            match_positions,
+            get_stored_revision(args, target_directory)?,
+        )?;
 
         let final_tree = tree.build();
 
@@ -371,19 +470,19 @@ impl ToTree for Vec<TransformedBranch> {
         nomad_style: &NomadStyle,
         target_directory: &str,
     ) -> Result<(StringItem, PrintConfig, Option<Vec<DirItem>>), NomadError> {
-        let labeled_items: HashMap<String, String> = HashMap::new();
-        let mut numbered_items: HashMap<String, String> = HashMap::new();
+        let mut numbered_branches: HashMap<String, String> = HashMap::new();
 
         let mut current_depth = 0;
         let mut num_branches = 0;
         let mut previous_item = &TransformedBranch {
-            components: vec![],
+            ahead_behind: None,
             depth: 0,
             full_branch: target_directory.to_string(),
             is_current_branch: false,
             is_end: false,
             is_head: false,
             is_parent: true,
+            last_commit_time: None,
             marker: Some("\u{f1d3}".to_string()), // 
             matched: None,
             upstream: None,
@@ -396,10 +495,8 @@ impl ToTree for Vec<TransformedBranch> {
         for item in self.iter() {
             check_nesting(
                 current_depth,
-                Path::new(&item.components.join("/")),
-                &nomad_mode,
-                Path::new(&previous_item.components.join("/")),
-                target_directory,
+                item.depth as usize,
+                previous_item.is_parent,
                 &mut tree,
             );
 
@@ -415,7 +512,7 @@ impl ToTree for Vec<TransformedBranch> {
                     )
                 ));
             } else if item.is_end {
-                numbered_items.insert(format!("{num_branches}"), item.full_branch.to_string());
+                numbered_branches.insert(format!("{num_branches}"), item.full_branch.to_string());
 
                 let number = if args.labels.numbers {
                     Some(num_branches)
@@ -432,7 +529,7 @@ impl ToTree for Vec<TransformedBranch> {
             previous_item = item;
         }
 
-        store_directory_contents(labeled_items, numbered_items)?;
+        store_branch_numbers(numbered_branches)?;
 
         let final_tree = tree.build();
 
@@ -448,3 +545,470 @@ impl ToTree for Vec<TransformedBranch> {
         Ok((final_tree, config, None))
     }
 }
+
+/// Converts a `Vec<TransformedItem>` into a nested `JsonItem` that may be serialized.
+pub trait ToJson {
+    /// Convert the `Vec<TransformedItem>` to a `JsonItem` for the target directory.
+    fn to_json(&self, args: &GlobalArgs, target_directory: &str) -> Result<JsonItem, NomadError>;
+}
+
+impl ToJson for Vec<TransformedItem> {
+    /// Build a JSON tree from the `Vec<TransformedItem>`.
+    ///
+    /// Labels and numbers are assigned in the same order as the standard tree and are
+    /// stored as well, so they may be used with subcommands such as `nd edit`.
+    fn to_json(&self, args: &GlobalArgs, target_directory: &str) -> Result<JsonItem, NomadError> {
+        let mut numbered_items: HashMap<String, String> = HashMap::new();
+        let mut labeled_items: HashMap<String, String> = HashMap::new();
+        let mut match_positions: HashMap<String, (usize, usize)> = HashMap::new();
+
+        let mut letter_index = 0; // The index pointing to a letter in the alphabet.
+        let mut loop_count = 0; // Count the number of times the alphabet has been looped.
+        let mut num_files = 0;
+
+        let tokei = if args.meta.tokei {
+            Some(loc_in_dir(target_directory))
+        } else {
+            None
+        };
+
+        // The root directory is always the first directory in this stack.
+        let mut open_directories = vec![JsonItem {
+            children: Some(Vec::new()),
+            churn: None,
+            depth: 0,
+            git_status: None,
+            label: None,
+            last_commit: None,
+            matched: None,
+            metadata: if args.meta.metadata {
+                get_json_metadata(Path::new(target_directory))
+            } else {
+                None
+            },
+            name: Path::new(target_directory)
+                .file_name()
+                .unwrap_or_else(|| OsStr::new("?"))
+                .to_str()
+                .unwrap_or("?")
+                .to_string(),
+            number: None,
+            owners: None,
+            path: target_directory.to_string(),
+            tokei: None,
+            line_matches: None,
+            item_type: JsonItemType::Directory,
+        }];
+
+        // Directories are compacted the same way as the standard tree so that labels
+        // resolve to the same directories.
+        let layout = if args.modifiers.compact_dirs {
+            get_compacted_layout(self)
+        } else {
+            self.iter()
+                .map(|item| Some((item.depth, item.name_components)))
+                .collect()
+        };
+
+        for (item, (depth, name_components)) in self
+            .iter()
+            .zip(layout)
+            .filter_map(|(item, layout)| layout.map(|layout| (item, layout)))
+        {
+            close_json_directories(&mut open_directories, depth as usize);
+
+            let metadata = match (args.meta.metadata, &item.revision_metadata) {
+                (true, Some(revision_metadata)) => Some(JsonMetadata {
+                    group: None,
+                    last_modified: revision_metadata
+                        .last_commit
+                        .as_ref()
+                        .and_then(|last_commit| u64::try_from(last_commit.time).ok()),
+                    mode: None,
+                    size: revision_metadata.size,
+                    user: None,
+                }),
+                (true, None) => get_json_metadata(Path::new(&item.path)),
+                (false, _) => None,
+            };
+            let last_commit = item.last_commit.as_ref().map(|last_commit| JsonLastCommit {
+                id: last_commit.id.clone(),
+                author: last_commit.author.clone(),
+                time: last_commit.time,
+                summary: last_commit.summary.clone(),
+            });
+            let churn = item.churn.map(|churn| {
+                let line_changes = item.line_changes.unwrap_or_default();
+
+                JsonChurn {
+                    commits: churn.commits,
+                    added: line_changes.added,
+                    deleted: line_changes.deleted,
+                }
+            });
+            let owners = item.owners.as_ref().map(|item_owners| {
+                item_owners
+                    .owners
+                    .iter()
+                    .map(|(name, lines)| JsonOwner {
+                        name: name.to_string(),
+                        lines: *lines,
+                        percentage: get_percentage(*lines, item_owners.total_lines),
+                    })
+                    .collect()
+            });
+            let name =
+                item.components[item.components.len().saturating_sub(name_components)..].join("/");
+
+            if item.is_dir {
+                let directory_label = get_directory_label(&mut letter_index, &mut loop_count);
+
+                labeled_items.insert(directory_label.to_string(), item.path.to_string());
+
+                open_directories.push(JsonItem {
+                    children: Some(Vec::new()),
+                    churn,
+                    depth,
+                    git_status: None,
+                    label: Some(directory_label),
+                    last_commit,
+                    matched: None,
+                    metadata,
+                    name,
+                    number: None,
+                    owners,
+                    path: item.path.to_string(),
+                    tokei: None,
+                    line_matches: None,
+                    item_type: JsonItemType::Directory,
+                });
+            } else if item.is_file && !args.modifiers.dirs {
+                numbered_items.insert(format!("{num_files}"), item.path.to_string());
+                if let Some(line_match) = item.line_matches.first() {
+                    match_positions.insert(
+                        item.path.to_string(),
+                        (line_match.line_number, line_match.column),
+                    );
+                }
+
+                if let Some(directory) = open_directories.last_mut() {
+                    directory
+                        .children
+                        .get_or_insert_with(Vec::new)
+                        .push(JsonItem {
+                            children: None,
+                            churn,
+                            depth,
+                            git_status: if args.style.no_git {
+                                None
+                            } else {
+                                item.status.and_then(get_git_status)
+                            },
+                            label: None,
+                            last_commit,
+                            matched: item.matched.map(|(start, end)| MatchSpan { start, end }),
+                            metadata,
+                            name,
+                            number: Some(num_files),
+                            owners,
+                            tokei: tokei
+                                .as_ref()
+                                .and_then(|tokei| loc_json_in_file(&item.path, tokei)),
+                            line_matches: if item.line_matches.is_empty() {
+                                None
+                            } else {
+                                Some(item.line_matches.clone())
+                            },
+                            path: item.path.to_string(),
+                            item_type: JsonItemType::File,
+                        });
+                }
+
+                num_files += 1;
+            }
+        }
+
+        close_json_directories(&mut open_directories, 0);
+
+        store_directory_contents(
+            labeled_items,
+            numbered_items,
+            match_positions,
+            get_stored_revision(args, target_directory)?,
+        )?;
+
+        open_directories.pop().ok_or(NomadError::NothingFound)
+    }
+}
+
+/// Streams a tree to `stdout` while the directory is being walked.
+///
+/// Unlike `ToTree`, items are not buffered into a `ptree` `TreeBuilder`, so the
+/// streamed tree cannot be exported or displayed in Rootless mode.
+pub trait StreamTree {
+    /// Walk the directory and print each item as soon as it is visited.
+    ///
+    /// `types` must be the same filetypes the walker was built with.
+    fn stream_tree(
+        self,
+        args: &GlobalArgs,
+        nomad_style: &NomadStyle,
+        target_directory: &str,
+        types: Option<Types>,
+    ) -> Result<(), NomadError>;
+}
+
+impl StreamTree for Walk {
+    /// Stream the tree while walking the directory.
+    ///
+    /// A directory's children are listed with the same walk settings when the
+    /// directory is entered. This determines whether an item is the last item in
+    /// its directory without having to wait for the rest of the walk.
+    fn stream_tree(
+        self,
+        args: &GlobalArgs,
+        nomad_style: &NomadStyle,
+        target_directory: &str,
+        types: Option<Types>,
+    ) -> Result<(), NomadError> {
+        let regex_expression = if let Some(ref pattern) = args.regex.pattern {
+            match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(error) => return Err(NomadError::RegexError(error)),
+            }
+        } else {
+            None
+        };
+
+        let mut git_statuses: HashMap<String, Status> = HashMap::new();
+        extend_status_map(
+            &mut git_statuses,
+            args.modifiers.show_ignored,
+            target_directory,
+        );
+
+        let tokei = if args.meta.tokei {
+            Some(loc_in_dir(target_directory))
+        } else {
+            None
+        };
+
+        let mut numbered_items: HashMap<String, String> = HashMap::new();
+        let mut labeled_items: HashMap<String, String> = HashMap::new();
+
+        let mut letter_index = 0; // The index pointing to a letter in the alphabet.
+        let mut loop_count = 0; // Count the number of times the alphabet has been looped.
+        let mut num_directories = 0;
+        let mut num_files = 0;
+
+        let config = build_tree_style(nomad_style);
+        let prefixes = get_tree_prefixes(&config);
+        let paint_prefix = |prefix: String| -> String {
+            if config.should_style_output(OutputKind::Stdout) {
+                config.paint_branch(prefix).to_string()
+            } else {
+                prefix
+            }
+        };
+
+        let out = stdout();
+        let mut handle = out.lock();
+
+        writeln!(handle)?;
+        writeln!(
+            handle,
+            "{}",
+            get_tree_label(args, &NomadMode::Normal, Path::new(target_directory))
+        )?;
+
+        let mut open_directories = vec![OpenDirectory {
+            child_prefix: "".to_string(),
+            path: PathBuf::from(target_directory),
+            remaining: list_children(
+                args,
+                Path::new(target_directory),
+                &regex_expression,
+                target_directory,
+                &types,
+            ),
+        }];
+
+        let start = Instant::now();
+        for entry in self.filter_map(|dir_entry| dir_entry.ok()) {
+            if entry.depth() == 0 {
+                continue;
+            }
+
+            let item_path = entry.path();
+            let is_dir = item_path.is_dir();
+
+            if is_dir {
+                extend_status_map_at_root(
+                    &mut git_statuses,
+                    args.modifiers.show_ignored,
+                    item_path.to_str().unwrap_or("?"),
+                );
+            }
+
+            // Close every directory that has been fully walked.
+            while let Some(open_directory) = open_directories.last() {
+                if Some(open_directory.path.as_path()) == item_path.parent() {
+                    break;
+                }
+
+                open_directories.pop();
+            }
+
+            let parent = match open_directories.last_mut() {
+                Some(parent) => parent,
+                None => continue,
+            };
+
+            // Items that are not in the listing were filtered out and are not displayed.
+            let position = match parent.remaining.iter().position(|child| child == item_path) {
+                Some(position) => position,
+                None => continue,
+            };
+            parent.remaining.drain(..=position);
+
+            let (prefix, child_prefix) = if parent.remaining.is_empty() {
+                (
+                    format!("{}{}", parent.child_prefix, prefixes.last_regular),
+                    format!("{}{}", parent.child_prefix, prefixes.last_child),
+                )
+            } else {
+                (
+                    format!("{}{}", parent.child_prefix, prefixes.regular),
+                    format!("{}{}", parent.child_prefix, prefixes.child),
+                )
+            };
+
+            let matched = regex_expression.as_ref().and_then(|regex| {
+                regex
+                    .find(
+                        item_path
+                            .strip_prefix(target_directory)
+                            .unwrap_or_else(|_| Path::new("?"))
+                            .to_str()
+                            .unwrap_or("?"),
+                    )
+                    .map(|matched| (matched.start(), matched.end()))
+            });
+
+            if is_dir {
+                let directory_label = get_directory_label(&mut letter_index, &mut loop_count);
+
+                labeled_items.insert(
+                    directory_label.to_string(),
+                    item_path.to_str().unwrap_or("?").to_string(),
+                );
+
+                let label = if args.labels.label_directories || args.labels.all_labels {
+                    Some(directory_label)
+                } else {
+                    None
+                };
+
+                writeln!(
+                    handle,
+                    "{}{}",
+                    paint_prefix(prefix),
+                    format_directory(
+                        args,
+                        None,
+                        item_path,
+                        label,
+                        None,
+                        None,
+                        matched,
+                        1,
+                        nomad_style,
+                        None,
+                        None,
+                        None,
+                        target_directory,
+                    )
+                )?;
+
+                open_directories.push(OpenDirectory {
+                    child_prefix,
+                    path: item_path.to_path_buf(),
+                    remaining: if args.modifiers.max_depth == Some(entry.depth()) {
+                        VecDeque::new()
+                    } else {
+                        list_children(args, item_path, &regex_expression, target_directory, &types)
+                    },
+                });
+
+                num_directories += 1;
+            } else {
+                numbered_items.insert(
+                    format!("{num_files}"),
+                    item_path.to_str().unwrap_or("?").to_string(),
+                );
+
+                let number = if args.labels.numbers || args.labels.all_labels {
+                    Some(num_files)
+                } else {
+                    None
+                };
+
+                writeln!(
+                    handle,
+                    "{}{}",
+                    paint_prefix(prefix),
+                    format_content(
+                        args,
+                        None,
+                        git_statuses
+                            .get(
+                                &canonicalize_path(item_path.to_str().unwrap_or("?"))
+                                    .unwrap_or_else(|_| "?".to_string()),
+                            )
+                            .map(|status| get_status_marker(&args.style, nomad_style, *status)),
+                        get_file_icon(item_path),
+                        item_path,
+                        None,
+                        None,
+                        matched,
+                        nomad_style,
+                        number,
+                        None,
+                        None,
+                        target_directory,
+                    )
+                )?;
+
+                if let Some(ref tokei) = tokei {
+                    let stats = loc_in_file(args, item_path.to_str().unwrap_or("?"), tokei);
+
+                    for (index, stat) in stats.iter().enumerate() {
+                        let stat_prefix = if index == stats.len() - 1 {
+                            format!("{child_prefix}{}", prefixes.last_regular)
+                        } else {
+                            format!("{child_prefix}{}", prefixes.regular)
+                        };
+
+                        writeln!(handle, "{}{stat}", paint_prefix(stat_prefix))?;
+                    }
+                }
+
+                num_files += 1;
+            }
+        }
+
+        store_directory_contents(labeled_items, numbered_items, HashMap::new(), None)?;
+
+        writeln!(handle)?;
+
+        if args.statistics {
+            let duration = start.elapsed().as_millis();
+            writeln!(
+                handle,
+                "| {num_directories} directories | {num_files} files | {duration} ms |\n"
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -3,30 +3,51 @@
 use crate::{
     cli::global::GlobalArgs,
     errors::NomadError,
+    git::{markers::get_git_status, revision::resolve_revision},
+    loc::{loc_in_dir, loc_json_in_file},
+    models::{GitStatus, JsonItem, LineMatch, MatchSpan},
     style::models::NomadStyle,
     utils::{
         cache::{get_json_file, write_to_json},
-        meta::get_metadata,
+        meta::{convert_bytes, get_changed_time, get_disk_size, get_metadata},
+        open::get_deserialized_json,
     },
-    EXTENSION_ICON_MAP, NAME_ICON_MAP,
+    ALPHABET, EXTENSION_ICON_MAP, NAME_ICON_MAP,
 };
 
 use ansi_term::Colour;
 use anyhow::Result;
 use ignore::{
     types::{Types, TypesBuilder},
-    Walk, WalkBuilder,
+    DirEntry, Walk, WalkBuilder, WalkState,
 };
 use ptree::{Color, PrintConfig, Style, TreeBuilder};
+use regex::Regex;
+use serde::Serialize;
 use serde_json::{json, Value};
 
 use std::{
-    collections::HashMap,
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
     ffi::OsStr,
-    path::{Component, Path},
+    fs::read,
+    path::{Path, PathBuf},
+    sync::mpsc::channel,
+    time::UNIX_EPOCH,
 };
 
-use super::modes::NomadMode;
+use super::{
+    filters::get_metadata_filters,
+    models::{ItemSize, LineChanges, SortOptions, TransformedItem, TreePrefixes},
+    modes::{NomadMode, SortMode},
+};
+
+/// The number of bytes at the start of a file that are checked for NUL bytes to
+/// detect binary files.
+const BINARY_CHECK_LENGTH: usize = 8000;
+
+/// The number of heaviest directories listed in the statistics if a number is not set.
+const DEFAULT_TOP_DIRECTORIES: usize = 10;
 
 /// Contains options for `Types` building.
 pub enum TypeOption {
@@ -80,13 +101,29 @@ pub fn build_walker(
     target_directory: &str,
     types: Option<Types>,
 ) -> Result<Walk, NomadError> {
+    Ok(build_walk_builder(args, target_directory, types)?.build())
+}
+
+/// Build a `WalkBuilder` based on the client's CLI parameters.
+///
+/// The builder is returned instead of a `Walk` when the same settings are needed
+/// to build additional walkers, such as when the tree is streamed.
+pub fn build_walk_builder(
+    args: &GlobalArgs,
+    target_directory: &str,
+    types: Option<Types>,
+) -> Result<WalkBuilder, NomadError> {
     if Path::new(target_directory).is_dir() {
         let mut walk = WalkBuilder::new(target_directory);
 
+        // Files that are ignored by Git are walked so that they may be marked if
+        // ignored files were requested.
+        let respect_git_ignores = !(args.modifiers.disrespect || args.modifiers.show_ignored);
+
         walk.follow_links(true)
-            .git_exclude(!args.modifiers.disrespect)
-            .git_global(!args.modifiers.disrespect)
-            .git_ignore(!args.modifiers.disrespect)
+            .git_exclude(respect_git_ignores)
+            .git_global(respect_git_ignores)
+            .git_ignore(respect_git_ignores)
             .hidden(!args.modifiers.hidden)
             .ignore(!args.modifiers.disrespect)
             .max_depth(args.modifiers.max_depth)
@@ -97,13 +134,643 @@ pub fn build_walker(
         if let Some(types) = types {
             walk.types(types);
         }
+        if let Some(metadata_filters) = get_metadata_filters(args)? {
+            walk.filter_entry(move |entry| metadata_filters.matches(entry));
+        }
 
-        Ok(walk.build())
+        Ok(walk)
     } else {
         Err(NomadError::NotADirectory(target_directory.into()))
     }
 }
 
+/// Walk the directory and collect its entries, using multiple threads if more than one is set.
+///
+/// A parallel walk visits entries in no particular order, so the entries are sorted by
+/// their paths afterwards. This matches the order of a sequential walk, which keeps item
+/// labels the same regardless of the number of threads.
+pub fn collect_entries(walk_builder: &mut WalkBuilder, threads: Option<usize>) -> Vec<DirEntry> {
+    match threads {
+        Some(threads) if threads > 1 => {
+            let (sender, receiver) = channel();
+
+            walk_builder.threads(threads).build_parallel().run(|| {
+                let sender = sender.clone();
+
+                Box::new(move |dir_entry| {
+                    if let Ok(entry) = dir_entry {
+                        if sender.send(entry).is_err() {
+                            return WalkState::Quit;
+                        }
+                    }
+
+                    WalkState::Continue
+                })
+            });
+            drop(sender);
+
+            let mut entries = receiver.into_iter().collect::<Vec<DirEntry>>();
+            entries.sort_by(|a, b| a.path().cmp(b.path()));
+
+            entries
+        }
+        _ => walk_builder
+            .build()
+            .filter_map(|dir_entry| dir_entry.ok())
+            .collect(),
+    }
+}
+
+/// Search a file's contents for the regex and return every line that matched.
+///
+/// Returns `None` if the file does not contain a match, could not be read, or is
+/// a binary file. Files containing a NUL byte near the start or invalid UTF-8 are
+/// considered binary.
+pub fn search_file_contents(path: &Path, regex: &Regex) -> Option<Vec<LineMatch>> {
+    let bytes = read(path).ok()?;
+    if bytes[..bytes.len().min(BINARY_CHECK_LENGTH)].contains(&0) {
+        return None;
+    }
+
+    let contents = String::from_utf8(bytes).ok()?;
+
+    let line_matches = contents
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let matches = regex
+                .find_iter(line)
+                .filter(|matched| !matched.as_str().is_empty())
+                .map(|matched| MatchSpan {
+                    start: matched.start(),
+                    end: matched.end(),
+                })
+                .collect::<Vec<MatchSpan>>();
+
+            let first_match = matches.first()?;
+
+            Some(LineMatch {
+                column: line[..first_match.start].chars().count() + 1,
+                line: line.to_string(),
+                line_number: index + 1,
+                matches,
+            })
+        })
+        .collect::<Vec<LineMatch>>();
+
+    if line_matches.is_empty() {
+        None
+    } else {
+        Some(line_matches)
+    }
+}
+
+/// Add up the sizes of every entry that was walked.
+///
+/// Files map to their own size and directories map to the total size of every
+/// item within them. The target directory is included as well.
+pub fn get_disk_usage(entries: &[DirEntry]) -> HashMap<PathBuf, ItemSize> {
+    let mut disk_usage: HashMap<PathBuf, ItemSize> = HashMap::new();
+
+    for entry in entries.iter() {
+        if entry.path().is_dir() {
+            disk_usage.entry(entry.path().to_path_buf()).or_default();
+        }
+
+        if entry.depth() == 0 {
+            continue;
+        }
+
+        if let Ok(metadata) = entry.metadata() {
+            let size = ItemSize {
+                apparent: metadata.len(),
+                disk: get_disk_size(&metadata),
+            };
+
+            if !metadata.is_dir() {
+                disk_usage.insert(entry.path().to_path_buf(), size);
+            }
+
+            for ancestor in entry.path().ancestors().skip(1).take(entry.depth()) {
+                let total = disk_usage.entry(ancestor.to_path_buf()).or_default();
+
+                total.apparent += size.apparent;
+                total.disk += size.disk;
+            }
+        }
+    }
+
+    disk_usage
+}
+
+/// Get the percentage of the total that the part makes up, rounded to one decimal
+/// place.
+pub fn get_percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        (part as f64 / total as f64 * 1000.0).round() / 10.0
+    }
+}
+
+/// Format an item's sizes, ie. `12 KB, 16 KB on disk`.
+pub fn format_item_size(args: &GlobalArgs, size: &ItemSize) -> String {
+    let plain_size = format!(
+        "{}, {} on disk",
+        convert_bytes(i64::try_from(size.apparent).unwrap_or(i64::MAX)).trim(),
+        convert_bytes(i64::try_from(size.disk).unwrap_or(i64::MAX)).trim()
+    );
+
+    if args.style.plain || args.style.no_colors {
+        plain_size
+    } else {
+        Colour::Fixed(172).paint(plain_size).to_string()
+    }
+}
+
+/// Get the sort settings from the CLI, falling back to the settings in `nomad.toml`.
+pub fn get_sort_options(args: &GlobalArgs, nomad_style: &NomadStyle) -> SortOptions {
+    SortOptions {
+        dirs_first: args.modifiers.dirs_first || nomad_style.tree.dirs_first,
+        reverse: args.modifiers.reverse || nomad_style.tree.reverse,
+        sort_mode: args
+            .modifiers
+            .sort
+            .or(nomad_style.tree.sort)
+            .unwrap_or(SortMode::Name),
+    }
+}
+
+/// Sort the items within each directory while keeping every directory's contents
+/// directly below it.
+///
+/// Items have to be sorted before labels and numbers are assigned so that they
+/// follow the order the tree is displayed in.
+pub fn sort_items(
+    items: Vec<TransformedItem>,
+    sort_options: &SortOptions,
+    target_directory: &str,
+) -> Vec<TransformedItem> {
+    if let SortOptions {
+        dirs_first: false,
+        reverse: false,
+        sort_mode: SortMode::Name,
+    } = sort_options
+    {
+        return items;
+    }
+
+    let mut children = get_item_children(&items);
+    let root = items.len();
+
+    let weights = get_sort_weights(&items, &children, sort_options.sort_mode, target_directory);
+
+    for siblings in children.iter_mut() {
+        siblings.sort_by(|a, b| {
+            let (a_item, b_item) = (&items[*a], &items[*b]);
+            let (a_name, b_name) = (
+                a_item.components.last().map_or("", |name| name.as_str()),
+                b_item.components.last().map_or("", |name| name.as_str()),
+            );
+
+            let ordering = match sort_options.sort_mode {
+                SortMode::Extension => Path::new(a_name)
+                    .extension()
+                    .cmp(&Path::new(b_name).extension())
+                    .then_with(|| a_name.cmp(b_name)),
+                SortMode::Name => a_name.cmp(b_name),
+                SortMode::Natural => compare_natural(a_name, b_name),
+                _ => weights[*b]
+                    .cmp(&weights[*a])
+                    .then_with(|| a_name.cmp(b_name)),
+            };
+            let ordering = if sort_options.reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+
+            if sort_options.dirs_first {
+                b_item.is_dir.cmp(&a_item.is_dir).then(ordering)
+            } else {
+                ordering
+            }
+        });
+    }
+
+    let mut order = Vec::with_capacity(items.len());
+    let mut stack = children[root].iter().rev().copied().collect::<Vec<usize>>();
+    while let Some(index) = stack.pop() {
+        order.push(index);
+        stack.extend(children[index].iter().rev());
+    }
+
+    let mut items = items
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<Option<TransformedItem>>>();
+
+    order
+        .into_iter()
+        .filter_map(|index| items[index].take())
+        .collect()
+}
+
+/// Get the indices of the items directly within each directory. The items directly
+/// within the target directory are stored at the last index.
+fn get_item_children(items: &[TransformedItem]) -> Vec<Vec<usize>> {
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); items.len() + 1];
+    let mut open_directories: Vec<usize> = Vec::new();
+
+    for (index, item) in items.iter().enumerate() {
+        while let Some(directory) = open_directories.last() {
+            if items[*directory].depth < item.depth {
+                break;
+            }
+
+            open_directories.pop();
+        }
+
+        children[*open_directories.last().unwrap_or(&items.len())].push(index);
+
+        if item.is_dir {
+            open_directories.push(index);
+        }
+    }
+
+    children
+}
+
+/// Get the depth and number of name components of each item once chains of
+/// directories that only contain a single directory are collapsed.
+///
+/// Collapsed directories are `None`. The deepest directory in each chain is kept, so
+/// its label resolves to the deepest directory. Everything within the chain is moved
+/// up to the depth of the first directory in the chain.
+pub fn get_compacted_layout(items: &[TransformedItem]) -> Vec<Option<(i32, usize)>> {
+    let children = get_item_children(items);
+
+    let mut is_collapsed = vec![false; items.len()];
+    let mut name_components = vec![1; items.len()];
+    let mut depth_offsets = vec![0; items.len()];
+
+    // Every directory comes before its contents, so each directory's offset and name
+    // are final by the time its contents are visited.
+    for (index, item) in items.iter().enumerate() {
+        if let (true, [child]) = (item.is_dir, &children[index][..]) {
+            if items[*child].is_dir {
+                is_collapsed[index] = true;
+                name_components[*child] = name_components[index] + 1;
+            }
+        }
+
+        let offset = depth_offsets[index] + usize::from(is_collapsed[index]);
+        for child in children[index].iter() {
+            depth_offsets[*child] = offset;
+        }
+    }
+
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            (!is_collapsed[index]).then(|| {
+                (
+                    item.depth - depth_offsets[index] as i32,
+                    name_components[index],
+                )
+            })
+        })
+        .collect()
+}
+
+/// Collapse chains of directories that only contain a single directory into one
+/// item, ie. `src/main/java`.
+pub fn compact_directories(items: Vec<TransformedItem>) -> Vec<TransformedItem> {
+    let layout = get_compacted_layout(&items);
+
+    items
+        .into_iter()
+        .zip(layout)
+        .filter_map(|(mut item, layout)| {
+            let (depth, name_components) = layout?;
+            item.depth = depth;
+            item.name_components = name_components;
+
+            Some(item)
+        })
+        .collect()
+}
+
+/// Set the number of lines that were added and deleted in each file. Directories
+/// are set to the totals of every file within them.
+pub fn set_line_changes(
+    items: &mut [TransformedItem],
+    file_changes: &HashMap<String, LineChanges>,
+    target_directory: &str,
+) {
+    let mut item_changes: HashMap<&Path, LineChanges> = HashMap::new();
+    for (path, line_changes) in file_changes.iter() {
+        for ancestor in Path::new(path).ancestors() {
+            if ancestor == Path::new(target_directory) {
+                break;
+            }
+
+            let total = item_changes.entry(ancestor).or_default();
+            total.added += line_changes.added;
+            total.deleted += line_changes.deleted;
+        }
+    }
+
+    for item in items.iter_mut() {
+        item.line_changes = item_changes.get(Path::new(&item.path)).copied();
+    }
+}
+
+/// Remove directories that do not contain any files, directly or within their
+/// subdirectories. Returns the remaining items and the number of directories that
+/// were removed.
+pub fn prune_directories(items: Vec<TransformedItem>) -> (Vec<TransformedItem>, usize) {
+    let children = get_item_children(&items);
+
+    let mut is_pruned = vec![false; items.len()];
+
+    // Every directory comes before its contents, so visiting the items in reverse
+    // decides whether each subdirectory is pruned before its parent directory.
+    for index in (0..items.len()).rev() {
+        if items[index].is_dir {
+            is_pruned[index] = children[index]
+                .iter()
+                .all(|child| items[*child].is_dir && is_pruned[*child]);
+        }
+    }
+
+    let num_pruned = is_pruned.iter().filter(|pruned| **pruned).count();
+
+    (
+        items
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !is_pruned[*index])
+            .map(|(_, item)| item)
+            .collect(),
+        num_pruned,
+    )
+}
+
+/// Find the items that are hidden because their directory contains more than
+/// `max_items` items. Only directories count towards the limit if `dirs_only` is set.
+///
+/// The number of hidden directories and files in each directory are also returned.
+/// They are keyed by the index of the first hidden item in the directory, which is
+/// where the summary of the hidden items is displayed.
+pub fn get_hidden_items(
+    items: &[TransformedItem],
+    max_items: usize,
+    dirs_only: bool,
+) -> (Vec<bool>, HashMap<usize, (usize, usize)>) {
+    let children = get_item_children(items);
+
+    let mut hidden_items = vec![false; items.len()];
+    let mut hidden_summaries = HashMap::new();
+
+    // The root of the tree comes first. Every other directory comes before its
+    // contents, so whether the directory itself is hidden is already known.
+    for directory in [items.len()].into_iter().chain(0..items.len()) {
+        let is_hidden = directory < items.len() && hidden_items[directory];
+
+        let mut first_hidden = None;
+        let (mut num_directories, mut num_files) = (0, 0);
+        let mut num_displayed = 0;
+
+        for child in children[directory].iter() {
+            if is_hidden {
+                hidden_items[*child] = true;
+            } else if items[*child].is_dir || !dirs_only {
+                if num_displayed < max_items {
+                    num_displayed += 1;
+                } else {
+                    hidden_items[*child] = true;
+                    first_hidden.get_or_insert(*child);
+
+                    if items[*child].is_dir {
+                        num_directories += 1;
+                    } else {
+                        num_files += 1;
+                    }
+                }
+            }
+        }
+
+        if let Some(first_hidden) = first_hidden {
+            hidden_summaries.insert(first_hidden, (num_directories, num_files));
+        }
+    }
+
+    (hidden_items, hidden_summaries)
+}
+
+/// Get the label of each item in the order they are displayed in the tree. Directories
+/// are labeled with letters and files are numbered, unless only directories are
+/// displayed.
+///
+/// Items hidden by the item limit are labeled as well, so they may still be used with
+/// subcommands.
+pub fn get_item_labels(items: &[TransformedItem], dirs_only: bool) -> Vec<Option<String>> {
+    let mut letter_index = 0; // The index pointing to a letter in the alphabet.
+    let mut loop_count = 0; // Count the number of times the alphabet has been looped.
+    let mut num_files = 0;
+
+    items
+        .iter()
+        .map(|item| {
+            if item.is_dir {
+                Some(get_directory_label(&mut letter_index, &mut loop_count))
+            } else if item.is_file && !dirs_only {
+                num_files += 1;
+
+                Some(format!("{}", num_files - 1))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Get the number each item is sorted by for sort modes that put the largest
+/// value first. Directories use the total (or the highest value) of their contents
+/// if the item's own value does not apply to directories.
+fn get_sort_weights(
+    items: &[TransformedItem],
+    children: &[Vec<usize>],
+    sort_mode: SortMode,
+    target_directory: &str,
+) -> Vec<u64> {
+    let get_timestamp = |item: &TransformedItem| -> u64 {
+        Path::new(&item.path)
+            .metadata()
+            .ok()
+            .and_then(|metadata| match sort_mode {
+                SortMode::Ctime => get_changed_time(&metadata),
+                _ => metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map(|duration| duration.as_secs()),
+            })
+            .unwrap_or(0)
+    };
+
+    match sort_mode {
+        SortMode::Ctime | SortMode::Mtime => items.iter().map(get_timestamp).collect(),
+        SortMode::GitStatus | SortMode::Loc => {
+            let tokei = match sort_mode {
+                SortMode::Loc => Some(loc_in_dir(target_directory)),
+                _ => None,
+            };
+
+            let mut weights = vec![0; items.len()];
+
+            // Items are ordered so that every directory comes before its contents, so
+            // iterating backwards visits each directory's contents first.
+            for (index, item) in items.iter().enumerate().rev() {
+                weights[index] = if item.is_dir {
+                    let child_weights = children[index].iter().map(|child| weights[*child]);
+
+                    match sort_mode {
+                        SortMode::Loc => child_weights.sum(),
+                        _ => child_weights.max().unwrap_or(0),
+                    }
+                } else if let Some(ref tokei) = tokei {
+                    loc_json_in_file(&item.path, tokei).map_or(0, |stats| stats.code as u64)
+                } else {
+                    match item.status.and_then(get_git_status) {
+                        Some(GitStatus::Conflicted) => 6,
+                        Some(
+                            GitStatus::StagedAdded
+                            | GitStatus::StagedDeleted
+                            | GitStatus::StagedModified
+                            | GitStatus::StagedRenamed
+                            | GitStatus::StagedTypechanged,
+                        ) => 5,
+                        Some(GitStatus::Deleted) => 4,
+                        Some(GitStatus::Modified) => 3,
+                        Some(GitStatus::Renamed | GitStatus::Typechanged) => 2,
+                        Some(GitStatus::Untracked) => 1,
+                        Some(GitStatus::Ignored) | None => 0,
+                    }
+                };
+            }
+
+            weights
+        }
+        SortMode::Churn => items
+            .iter()
+            .map(|item| item.churn.map_or(0, |churn| churn.commits as u64))
+            .collect(),
+        SortMode::Size => items
+            .iter()
+            .map(|item| item.size.unwrap_or_default().apparent)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Compare names so that numbers within them are compared by their values, ie.
+/// `file2` comes before `file10`. Letters are compared without case.
+fn compare_natural(a: &str, b: &str) -> Ordering {
+    let (mut a_chars, mut b_chars) = (a.chars().peekable(), b.chars().peekable());
+
+    loop {
+        match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_char), Some(b_char)) if a_char.is_ascii_digit() && b_char.is_ascii_digit() => {
+                let mut a_number = String::new();
+                while let Some(digit) = a_chars.next_if(|character| character.is_ascii_digit()) {
+                    a_number.push(digit);
+                }
+                let mut b_number = String::new();
+                while let Some(digit) = b_chars.next_if(|character| character.is_ascii_digit()) {
+                    b_number.push(digit);
+                }
+
+                let (a_number, b_number) = (
+                    a_number.trim_start_matches('0'),
+                    b_number.trim_start_matches('0'),
+                );
+                let ordering = a_number
+                    .len()
+                    .cmp(&b_number.len())
+                    .then_with(|| a_number.cmp(b_number));
+
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(a_char), Some(b_char)) => {
+                let ordering = a_char.to_lowercase().cmp(b_char.to_lowercase());
+
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+/// Display the heaviest directories below the target directory, largest first.
+pub fn display_heaviest_directories(
+    args: &GlobalArgs,
+    disk_usage: &HashMap<PathBuf, ItemSize>,
+    target_directory: &str,
+) {
+    let mut directories = disk_usage
+        .iter()
+        .filter(|(path, _)| path.is_dir() && path.as_path() != Path::new(target_directory))
+        .collect::<Vec<(&PathBuf, &ItemSize)>>();
+
+    if directories.is_empty() {
+        return;
+    }
+
+    directories.sort_by(|(a_path, a_size), (b_path, b_size)| {
+        b_size
+            .apparent
+            .cmp(&a_size.apparent)
+            .then(a_path.cmp(b_path))
+    });
+
+    let header = "Heaviest directories";
+    println!(
+        "{}\n",
+        if args.style.plain || args.style.no_colors {
+            header.to_string()
+        } else {
+            Colour::Blue.bold().paint(header).to_string()
+        }
+    );
+
+    for (path, size) in directories
+        .into_iter()
+        .take(args.modifiers.top.unwrap_or(DEFAULT_TOP_DIRECTORIES))
+    {
+        println!(
+            "    {}  {}",
+            format_item_size(args, size),
+            path.strip_prefix(target_directory)
+                .unwrap_or(path)
+                .display()
+        );
+    }
+
+    println!();
+}
+
 /// Get the file's corresponding icon.
 pub fn get_file_icon(item_path: &Path) -> String {
     if let Some(icon) = EXTENSION_ICON_MAP.get(
@@ -134,6 +801,18 @@ pub fn build_tree(
     nomad_style: &NomadStyle,
     target_directory: &Path,
 ) -> (PrintConfig, TreeBuilder) {
+    let tree = TreeBuilder::new(get_tree_label(args, nomad_mode, target_directory));
+    let config = build_tree_style(nomad_style);
+
+    (config, tree)
+}
+
+/// Get the label that is displayed at the root of the tree.
+pub fn get_tree_label(
+    args: &GlobalArgs,
+    nomad_mode: &NomadMode,
+    target_directory: &Path,
+) -> String {
     let directory_icon = &"\u{f115}"; // 
 
     let plain_name = target_directory
@@ -167,6 +846,16 @@ pub fn build_tree(
     match nomad_mode {
         NomadMode::GitBranch => {}
         _ => {
+            if let Some(ref revision) = args.modifiers.rev {
+                tree_label = if args.style.plain || args.style.no_colors {
+                    format!("{tree_label} [{revision}]")
+                } else {
+                    format!(
+                        "{tree_label} [{}]",
+                        Colour::Fixed(172).bold().paint(revision)
+                    )
+                };
+            }
             if args.meta.metadata {
                 let metadata = get_metadata(args, target_directory);
                 tree_label = format!("{metadata} {tree_label}");
@@ -174,10 +863,7 @@ pub fn build_tree(
         }
     }
 
-    let tree = TreeBuilder::new(tree_label);
-    let config = build_tree_style(nomad_style);
-
-    (config, tree)
+    tree_label
 }
 
 /// Build a new `Style` based on the settings in `NomadStyle`.
@@ -202,61 +888,176 @@ pub fn build_tree_style(nomad_style: &NomadStyle) -> PrintConfig {
     config
 }
 
+/// Build the prefixes that are drawn in front of each item in a streamed tree.
+///
+/// This follows the same rules `ptree` uses to draw its branches.
+pub fn get_tree_prefixes(config: &PrintConfig) -> TreePrefixes {
+    let item_padding = config.characters.empty.repeat(config.padding);
+    let pad_length = config.indent.saturating_sub(1 + config.padding);
+
+    let right_padding = config.characters.right.repeat(pad_length);
+    let empty_padding = config.characters.empty.repeat(pad_length);
+
+    TreePrefixes {
+        regular: format!(
+            "{}{right_padding}{item_padding}",
+            config.characters.down_and_right
+        ),
+        child: format!("{}{empty_padding}{item_padding}", config.characters.down),
+        last_regular: format!(
+            "{}{right_padding}{item_padding}",
+            config.characters.turn_right
+        ),
+        last_child: format!("{}{empty_padding}{item_padding}", config.characters.empty),
+    }
+}
+
+/// Get the next directory label and advance the label counters.
+///
+/// Labels go through the alphabet, then loop back around with a number appended
+/// to the letter, ie. `a`, `b`, ..., `z`, `a1`, `b1`, ...
+pub fn get_directory_label(letter_index: &mut usize, loop_count: &mut usize) -> String {
+    if *letter_index == 26 {
+        *loop_count += 1;
+        *letter_index = 0;
+    }
+
+    let mut directory_label = ALPHABET.get(*letter_index).unwrap_or(&'?').to_string();
+
+    if *loop_count > 0 {
+        directory_label.push_str(&loop_count.to_string());
+    }
+
+    *letter_index += 1;
+
+    directory_label
+}
+
+/// List the items directly within a directory that will be displayed in a streamed tree.
+///
+/// The listing is built with the same settings as the main walker so that it
+/// respects the same ignore rules and filetypes. Items are listed by file path, since
+/// the sort options cannot be used with `--stream`.
+///
+/// Unlike the standard tree, every subdirectory is listed even if nothing within it
+/// matches `-p/--pattern`, since its contents have not been visited yet.
+pub fn list_children(
+    args: &GlobalArgs,
+    directory: &Path,
+    regex_expression: &Option<Regex>,
+    target_directory: &str,
+    types: &Option<Types>,
+) -> VecDeque<PathBuf> {
+    let mut children_walker =
+        match build_walk_builder(args, directory.to_str().unwrap_or("?"), types.clone()) {
+            Ok(walk_builder) => walk_builder,
+            Err(_) => return VecDeque::new(),
+        };
+
+    children_walker
+        .max_depth(Some(1))
+        .build()
+        .filter_map(|dir_entry| {
+            if let Ok(entry) = dir_entry {
+                if entry.depth() == 0 {
+                    None
+                } else if entry.path().is_dir() {
+                    Some(entry.into_path())
+                } else if args.modifiers.dirs {
+                    None
+                } else if let Some(ref regex) = regex_expression {
+                    if regex.is_match(
+                        entry
+                            .path()
+                            .strip_prefix(target_directory)
+                            .unwrap_or_else(|_| Path::new("?"))
+                            .to_str()
+                            .unwrap_or("?"),
+                    ) {
+                        Some(entry.into_path())
+                    } else {
+                        None
+                    }
+                } else {
+                    Some(entry.into_path())
+                }
+            } else {
+                None
+            }
+        })
+        .collect::<VecDeque<PathBuf>>()
+}
+
 /// Run checks to ensure tree nesting is correct. Make any corrections if applicable.
+///
+/// `current_depth` is the depth of the previous item. A directory opens a new level
+/// in the tree, so it has to be closed as well if the item is not inside it.
 pub fn check_nesting(
     current_depth: usize,
-    item: &Path,
-    nomad_mode: &NomadMode,
-    previous_item: &Path,
-    target_directory: &str,
+    item_depth: usize,
+    previous_is_dir: bool,
     tree: &mut TreeBuilder,
 ) {
-    let mut item_depth = 0;
-    let item_components = match nomad_mode {
-        NomadMode::GitBranch => item.components(),
-        _ => item
-            .strip_prefix(target_directory)
-            .unwrap_or_else(|_| Path::new("?"))
-            .components(),
-    };
-
-    for component in item_components {
-        if let Component::Normal(_) = component {
-            item_depth += 1;
-        }
-    }
-
     if item_depth < current_depth {
-        if previous_item.is_dir() {
-            let item_parent = item
-                .parent()
-                .expect("Could not get the current item's parent!");
-            let previous_parent = previous_item
-                .parent()
-                .expect("Could not get the previous item's parent!");
-
-            if item_parent != previous_parent {
-                tree.end_child();
-            }
+        if previous_is_dir {
+            tree.end_child();
         }
 
         for _ in 0..current_depth - item_depth {
             tree.end_child();
         }
-    } else if item_depth == current_depth && previous_item.is_dir() {
+    } else if item_depth == current_depth && previous_is_dir {
         tree.end_child();
     }
 }
 
+/// Close open JSON directories until the directory containing an item at this depth
+/// is the last open directory. The root of the tree is never closed.
+pub fn close_json_directories(open_directories: &mut Vec<JsonItem>, depth: usize) {
+    while open_directories.len() > depth.max(1) {
+        if let Some(directory) = open_directories.pop() {
+            if let Some(parent) = open_directories.last_mut() {
+                parent.children.get_or_insert_with(Vec::new).push(directory);
+            }
+        }
+    }
+}
+
+/// Get the SHA of the revision the tree was built from. Returns `None` if the tree
+/// was built from the filesystem.
+pub fn get_stored_revision(
+    args: &GlobalArgs,
+    target_directory: &str,
+) -> Result<Option<String>, NomadError> {
+    args.modifiers
+        .rev
+        .as_ref()
+        .map(|revision| resolve_revision(revision, target_directory))
+        .transpose()
+}
+
 /// Write the labeled directories or numbered directory contents to a temporary file.
+///
+/// The position of the first content match in each file is stored as well so that
+/// editors may be opened at the match. If the tree was built from a Git revision,
+/// its SHA is stored so that files are read at that revision.
+///
+/// Branch numbers do not refer to the directory contents, so the stored branch
+/// numbers are kept.
 pub fn store_directory_contents(
     labeled_items: HashMap<String, String>,
     numbered_items: HashMap<String, String>,
+    match_positions: HashMap<String, (usize, usize)>,
+    revision: Option<String>,
 ) -> Result<(), NomadError> {
-    let mut json = json!({ "labeled": {}, "numbered": {} });
+    let branches = get_deserialized_json()
+        .map(|contents| contents.branches)
+        .unwrap_or_default();
+    let mut json = json!({ "branches": branches, "labeled": {}, "numbered": {}, "positions": {}, "revision": revision });
 
     write_map(labeled_items, &mut json, "labeled");
     write_map(numbered_items, &mut json, "numbered");
+    write_map(match_positions, &mut json, "positions");
 
     let mut json_file = get_json_file(false)?;
     write_to_json(&mut json_file, json)?;
@@ -264,12 +1065,459 @@ pub fn store_directory_contents(
     Ok(())
 }
 
+/// Write the numbered branch names to the temporary file. The stored directory
+/// contents are kept so that file labels may still be used afterwards.
+pub fn store_branch_numbers(numbered_branches: HashMap<String, String>) -> Result<(), NomadError> {
+    let mut contents = get_deserialized_json().unwrap_or_default();
+    contents.branches = numbered_branches;
+
+    write_to_json(&mut get_json_file(false)?, serde_json::to_value(contents)?)
+}
+
 /// Write each key, value within a HashMap to JSON `Value` object.
-fn write_map(items: HashMap<String, String>, json: &mut Value, target_key: &str) {
+fn write_map<T: Serialize>(items: HashMap<String, T>, json: &mut Value, target_key: &str) {
     for (key, value) in items.iter() {
         json[target_key]
             .as_object_mut()
             .unwrap()
-            .insert(key.clone(), json!(value.clone()));
+            .insert(key.clone(), json!(value));
+    }
+}
+
+#[cfg(test)]
+mod test_utils {
+    use super::*;
+    use crate::{traverse::format::format_hidden_summary, utils::paths::get_last_components};
+
+    use structopt::StructOpt;
+
+    /// Create an item at the path relative to the target directory `/root`.
+    fn item(path: &str, is_dir: bool) -> TransformedItem {
+        let components = path
+            .split('/')
+            .map(|component| component.to_string())
+            .collect::<Vec<String>>();
+
+        TransformedItem {
+            churn: None,
+            depth: components.len() as i32,
+            components,
+            is_dir,
+            is_file: !is_dir,
+            last_commit: None,
+            line_changes: None,
+            line_matches: Vec::new(),
+            marker: None,
+            matched: None,
+            name_components: 1,
+            owners: None,
+            path: format!("/root/{path}"),
+            revision_metadata: None,
+            size: None,
+            status: None,
+        }
+    }
+
+    /// Sort the items and get their paths relative to the target directory.
+    fn sorted_paths(
+        items: Vec<TransformedItem>,
+        dirs_first: bool,
+        reverse: bool,
+        sort_mode: SortMode,
+    ) -> Vec<String> {
+        let sort_options = SortOptions {
+            dirs_first,
+            reverse,
+            sort_mode,
+        };
+
+        sort_items(items, &sort_options, "/root")
+            .into_iter()
+            .map(|item| item.components.join("/"))
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_natural_numbers() {
+        assert_eq!(compare_natural("file2", "file10"), Ordering::Less);
+        assert_eq!(compare_natural("file10", "file2"), Ordering::Greater);
+        assert_eq!(compare_natural("v1.10", "v1.9"), Ordering::Greater);
+        assert_eq!(compare_natural("file", "file1"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_compare_natural_leading_zeros() {
+        assert_eq!(compare_natural("file007", "file8"), Ordering::Less);
+        assert_eq!(compare_natural("file01b", "file1c"), Ordering::Less);
+
+        // Equal values are ordered by their full names so the order is stable.
+        assert_eq!(compare_natural("file01", "file1"), Ordering::Less);
+        assert_eq!(compare_natural("file1", "file01"), Ordering::Greater);
+        assert_eq!(compare_natural("0", "00"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_compare_natural_long_digit_runs() {
+        assert_eq!(
+            compare_natural("file99999999999999999999", "file100000000000000000000"),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_natural("file123456789012345678901", "file123456789012345678900"),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_natural("file00000000000000000000000001", "file2"),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_compare_natural_case() {
+        assert_eq!(compare_natural("apple", "Banana"), Ordering::Less);
+        assert_eq!(compare_natural("Apple", "banana"), Ordering::Less);
+        assert_eq!(compare_natural("A", "a"), Ordering::Less);
+        assert_eq!(compare_natural("a", "a"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_sort_items_keeps_contents_below_directories() {
+        let items = vec![
+            item("b", true),
+            item("b/z.txt", false),
+            item("b/a.txt", false),
+            item("a.txt", false),
+        ];
+
+        assert_eq!(
+            sorted_paths(items, false, true, SortMode::Name),
+            vec!["b", "b/z.txt", "b/a.txt", "a.txt"]
+        );
+    }
+
+    #[test]
+    fn test_sort_items_natural() {
+        let items = vec![
+            item("file10", false),
+            item("file2", false),
+            item("File1", false),
+        ];
+
+        assert_eq!(
+            sorted_paths(items, false, false, SortMode::Natural),
+            vec!["File1", "file2", "file10"]
+        );
+    }
+
+    #[test]
+    fn test_sort_items_dirs_first() {
+        let items = vec![
+            item("a.txt", false),
+            item("b", true),
+            item("b/c.txt", false),
+            item("z", true),
+        ];
+
+        assert_eq!(
+            sorted_paths(items, true, false, SortMode::Name),
+            vec!["b", "b/c.txt", "z", "a.txt"]
+        );
+    }
+
+    #[test]
+    fn test_sort_items_dirs_first_reverse() {
+        let items = vec![
+            item("a.txt", false),
+            item("b", true),
+            item("b/c.txt", false),
+            item("b/d.txt", false),
+            item("y.txt", false),
+            item("z", true),
+        ];
+
+        // Directories stay first when the order within files and directories is
+        // reversed.
+        assert_eq!(
+            sorted_paths(items, true, true, SortMode::Name),
+            vec!["z", "b", "b/d.txt", "b/c.txt", "y.txt", "a.txt"]
+        );
+    }
+
+    #[test]
+    fn test_sort_items_default_order_is_unchanged() {
+        let items = vec![item("b", false), item("a", false)];
+
+        assert_eq!(
+            sorted_paths(items, false, false, SortMode::Name),
+            vec!["b", "a"]
+        );
+    }
+
+    /// Compact the items and get their relative paths, depths, and names.
+    fn compacted(items: Vec<TransformedItem>) -> Vec<(String, i32, String)> {
+        compact_directories(items)
+            .into_iter()
+            .map(|item| {
+                let path = item.components.join("/");
+                let name = get_last_components(&path, item.name_components);
+
+                (path, item.depth, name)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compact_directories_collapses_chain() {
+        let items = vec![
+            item("src", true),
+            item("src/main", true),
+            item("src/main/java", true),
+            item("src/main/java/App.java", false),
+            item("src/main/java/Util.java", false),
+            item("README.md", false),
+        ];
+
+        assert_eq!(
+            compacted(items),
+            vec![
+                ("src/main/java".to_string(), 1, "src/main/java".to_string()),
+                (
+                    "src/main/java/App.java".to_string(),
+                    2,
+                    "App.java".to_string()
+                ),
+                (
+                    "src/main/java/Util.java".to_string(),
+                    2,
+                    "Util.java".to_string()
+                ),
+                ("README.md".to_string(), 1, "README.md".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compact_directories_offsets_nested_chains() {
+        let items = vec![
+            item("a", true),
+            item("a/b", true),
+            item("a/b/c", true),
+            item("a/b/c/d", true),
+            item("a/b/c/d/e", true),
+            item("a/b/c/d/e/f.txt", false),
+            item("a/b/c/g.txt", false),
+        ];
+
+        // `a/b/c` stops the first chain since it contains two items, and `d/e` is
+        // collapsed within it.
+        assert_eq!(
+            compacted(items),
+            vec![
+                ("a/b/c".to_string(), 1, "a/b/c".to_string()),
+                ("a/b/c/d/e".to_string(), 2, "d/e".to_string()),
+                ("a/b/c/d/e/f.txt".to_string(), 3, "f.txt".to_string()),
+                ("a/b/c/g.txt".to_string(), 2, "g.txt".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compact_directories_keeps_single_files_and_empty_directories() {
+        let items = vec![
+            item("a", true),
+            item("a/b.txt", false),
+            item("c", true),
+            item("c/d", true),
+        ];
+
+        // Directories that only contain a file are not collapsed, and an empty
+        // directory ends a chain.
+        assert_eq!(
+            compacted(items),
+            vec![
+                ("a".to_string(), 1, "a".to_string()),
+                ("a/b.txt".to_string(), 2, "b.txt".to_string()),
+                ("c/d".to_string(), 1, "c/d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compact_directories_keeps_highlight_spans() {
+        let mut items = vec![
+            item("src", true),
+            item("src/main", true),
+            item("src/main/lib.rs", false),
+        ];
+        items[1].matched = Some((4, 8));
+
+        let items = compact_directories(items);
+        let path = items[0].components.join("/");
+        let name = get_last_components(&path, items[0].name_components);
+
+        // The span is relative to the whole path, which lines up with the collapsed
+        // name since the name ends at the deepest directory.
+        assert_eq!(items[0].matched, Some((4, 8)));
+        assert!(path.len() - name.len() <= 4);
+        assert_eq!(&path[4..8], "main");
+    }
+
+    #[test]
+    fn test_get_compacted_layout_matches_compact_directories() {
+        let items = vec![item("x", true), item("x/y", true), item("x/y/z.txt", false)];
+
+        assert_eq!(
+            get_compacted_layout(&items),
+            vec![None, Some((1, 2)), Some((2, 1))]
+        );
+    }
+
+    /// Get the paths of the hidden items and the summaries of the hidden items, keyed
+    /// by the path of the item the summary replaces.
+    fn hidden(
+        items: &[TransformedItem],
+        max_items: usize,
+        dirs_only: bool,
+    ) -> (Vec<String>, Vec<(String, (usize, usize))>) {
+        let (hidden_items, hidden_summaries) = get_hidden_items(items, max_items, dirs_only);
+
+        let hidden_paths = items
+            .iter()
+            .zip(hidden_items)
+            .filter(|(_, is_hidden)| *is_hidden)
+            .map(|(item, _)| item.components.join("/"))
+            .collect();
+        let mut summaries = hidden_summaries
+            .into_iter()
+            .map(|(index, counts)| (items[index].components.join("/"), counts))
+            .collect::<Vec<(String, (usize, usize))>>();
+        summaries.sort();
+
+        (hidden_paths, summaries)
+    }
+
+    #[test]
+    fn test_get_hidden_items_nested_directories() {
+        let items = vec![
+            item("a", true),
+            item("a/1.txt", false),
+            item("a/2.txt", false),
+            item("a/3.txt", false),
+            item("b", true),
+            item("b/c", true),
+            item("b/c/4.txt", false),
+            item("d", true),
+            item("e.txt", false),
+        ];
+
+        let (hidden_paths, summaries) = hidden(&items, 2, false);
+
+        // The contents of a hidden directory are hidden without being counted in the
+        // summary of its parent.
+        assert_eq!(hidden_paths, vec!["a/3.txt", "d", "e.txt"]);
+        assert_eq!(
+            summaries,
+            vec![("a/3.txt".to_string(), (0, 1)), ("d".to_string(), (1, 1))]
+        );
+    }
+
+    #[test]
+    fn test_get_hidden_items_hides_directory_contents() {
+        let items = vec![
+            item("a", false),
+            item("b", true),
+            item("b/c.txt", false),
+            item("b/d", true),
+            item("b/d/e.txt", false),
+        ];
+
+        let (hidden_paths, summaries) = hidden(&items, 1, false);
+
+        assert_eq!(hidden_paths, vec!["b", "b/c.txt", "b/d", "b/d/e.txt"]);
+        assert_eq!(summaries, vec![("b".to_string(), (1, 0))]);
+    }
+
+    #[test]
+    fn test_get_hidden_items_dirs_only() {
+        let items = vec![
+            item("a.txt", false),
+            item("b", true),
+            item("c.txt", false),
+            item("d", true),
+            item("e", true),
+            item("e/f", true),
+        ];
+
+        // Only directories count towards the limit, and files are never part of a
+        // summary.
+        let (hidden_paths, summaries) = hidden(&items, 2, true);
+
+        assert_eq!(hidden_paths, vec!["e", "e/f"]);
+        assert_eq!(summaries, vec![("e".to_string(), (1, 0))]);
+    }
+
+    #[test]
+    fn test_get_hidden_items_under_limit() {
+        let items = vec![
+            item("a", true),
+            item("a/b.txt", false),
+            item("c.txt", false),
+        ];
+
+        let (hidden_paths, summaries) = hidden(&items, 2, false);
+
+        assert!(hidden_paths.is_empty());
+        assert!(summaries.is_empty());
+    }
+
+    #[test]
+    fn test_get_item_labels_include_hidden_items() {
+        let items = vec![
+            item("a", true),
+            item("a/1.txt", false),
+            item("a/2.txt", false),
+            item("b", true),
+            item("b/3.txt", false),
+            item("c.txt", false),
+        ];
+
+        let (hidden_items, _) = get_hidden_items(&items, 1, false);
+        assert_eq!(hidden_items, vec![false, false, true, true, true, true]);
+
+        // Hidden items keep their place in the label order, so the labels of the
+        // displayed items are the same as when the tree is not limited.
+        assert_eq!(
+            get_item_labels(&items, false),
+            vec![
+                Some("a".to_string()),
+                Some("0".to_string()),
+                Some("1".to_string()),
+                Some("b".to_string()),
+                Some("2".to_string()),
+                Some("3".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_item_labels_dirs_only() {
+        let items = vec![item("a", true), item("a/1.txt", false), item("b", true)];
+
+        assert_eq!(
+            get_item_labels(&items, true),
+            vec![Some("a".to_string()), None, Some("b".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_format_hidden_summary() {
+        let args = GlobalArgs::from_iter(["nd", "--plain"]);
+
+        assert_eq!(format_hidden_summary(&args, 1, 0), "… 1 more (1 dir)");
+        assert_eq!(format_hidden_summary(&args, 0, 1), "… 1 more (1 file)");
+        assert_eq!(
+            format_hidden_summary(&args, 56, 1178),
+            "… 1,234 more (56 dirs, 1,178 files)"
+        );
     }
 }
@@ -12,7 +12,7 @@ use crate::{
     cli::global::GlobalArgs,
     errors::NomadError,
     style::models::NomadStyle,
-    traverse::{models::DirItem, modes::NomadMode, utils::build_walker, walk_directory},
+    traverse::{models::DirItem, modes::NomadMode, utils::build_walk_builder, walk_directory},
 };
 
 /// Return all app settings formatted in `Row`s.
@@ -101,12 +101,12 @@ pub fn get_tree(
     nomad_style: &NomadStyle,
     target_directory: &str,
 ) -> Result<(Vec<String>, Option<Vec<DirItem>>), NomadError> {
-    let (tree, config, directory_items) = walk_directory(
+    let (tree, config, directory_items, _) = walk_directory(
         args,
         NomadMode::Rootless,
         nomad_style,
         target_directory,
-        &mut build_walker(args, target_directory, None)?,
+        &mut build_walk_builder(args, target_directory, None)?,
     )?;
 
     // Write the tree to a buffer, then convert it to a `Vec<String>`.
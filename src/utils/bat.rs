@@ -1,6 +1,7 @@
 //! Run `bat`.
 
-use crate::errors::NomadError;
+use super::open::get_deserialized_json;
+use crate::{errors::NomadError, git::revision::get_blob_contents};
 
 use anyhow::Result;
 use bat::{Input, PagingMode, PrettyPrinter, WrappingMode};
@@ -8,20 +9,46 @@ use bat::{Input, PagingMode, PrettyPrinter, WrappingMode};
 use std::path::Path;
 
 /// Create a new `PrettyPrinter`, then run it against the file.
+///
+/// If the tree was built from a Git revision, the files are read at that revision
+/// instead of from the working directory.
 pub fn run_bat(found_items: Vec<String>) -> Result<(), NomadError> {
+    let revision = get_deserialized_json()
+        .ok()
+        .and_then(|contents| contents.revision);
+
+    let blobs = match revision {
+        Some(ref revision) => found_items
+            .iter()
+            .map(|path| get_blob_contents(path, revision))
+            .collect::<Result<Vec<Vec<u8>>, NomadError>>()?,
+        None => Vec::new(),
+    };
+
+    let inputs = match revision {
+        Some(ref revision) => found_items
+            .iter()
+            .zip(blobs.iter())
+            .map(|(path, blob)| {
+                Input::from_bytes(blob)
+                    .name(Path::new(path))
+                    .title(format!("{path} @ {}", &revision[..revision.len().min(7)]))
+            })
+            .collect::<Vec<Input>>(),
+        None => found_items
+            .iter()
+            .map(|path| Input::from_file(Path::new(path)))
+            .collect::<Vec<Input>>(),
+    };
+
     PrettyPrinter::new()
         .grid(true)
         .header(true)
-        .inputs(
-            found_items
-                .iter()
-                .map(|path| Input::from_file(Path::new(path)))
-                .collect::<Vec<Input>>(),
-        )
+        .inputs(inputs)
         .line_numbers(true)
         .paging_mode(PagingMode::QuitIfOneScreen)
         .true_color(true)
-        .vcs_modification_markers(true)
+        .vcs_modification_markers(revision.is_none())
         .wrapping_mode(WrappingMode::Character)
         .print()
         .map_or_else(|error| Err(NomadError::BatError(error)), |_| Ok(()))
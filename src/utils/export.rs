@@ -1,13 +1,23 @@
 //! Export a directory's tree to a file instead of saving.
 
-use crate::errors::NomadError;
+use crate::{
+    cli::global::GraphArgs,
+    errors::NomadError,
+    models::{JsonExport, JsonItem, JsonItemType},
+    style::models::NomadStyle,
+    utils::{
+        graph::{get_dot_export, get_mermaid_export},
+        paint::convert_ansi_to_hex,
+    },
+};
 
 use ansi_term::*;
 use anyhow::Result;
 use chrono::Local;
 use ptree::{item::StringItem, write_tree_with, PrintConfig};
+use regex::Regex;
 
-use std::{env, fs::File, io::Write};
+use std::{env, fs::File, io::Write, str::FromStr};
 
 /// Get the absolute path for the file name.
 fn get_absolute_path(file_name: &str) -> Result<String, NomadError> {
@@ -26,20 +36,84 @@ pub enum ExportMode<'a> {
     Normal,
     /// `nomad` was run in Git branch mode.
     GitBranch,
+    /// `nomad` was run in Git churn mode.
+    GitChurn,
+    /// `nomad` was run in Git owners mode.
+    GitOwners,
     /// `nomad` was run in Git status mode.
     GitStatus,
 }
 
+/// Variants for export formats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    /// Export a Graphviz (DOT) diagram.
+    Dot,
+    /// Export a colored tree with collapsible directories.
+    Html,
+    /// Export a nested JSON document.
+    Json,
+    /// Export the tree within a fenced code block.
+    Markdown,
+    /// Export a Mermaid flowchart.
+    Mermaid,
+    /// Export the tree as plain text.
+    Text,
+    /// Export an XML document that is compatible with `tree -X`.
+    Xml,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    /// Convert the value passed into `--export-format` into an `ExportFormat`.
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "dot" => Ok(ExportFormat::Dot),
+            "html" => Ok(ExportFormat::Html),
+            "json" => Ok(ExportFormat::Json),
+            "md" => Ok(ExportFormat::Markdown),
+            "mermaid" => Ok(ExportFormat::Mermaid),
+            "txt" => Ok(ExportFormat::Text),
+            "xml" => Ok(ExportFormat::Xml),
+            _ => Err(format!("{format} is not a supported export format")),
+        }
+    }
+}
+
+impl ExportFormat {
+    /// Check whether this format is built from a `JsonItem` instead of the tree.
+    pub fn is_structured(&self) -> bool {
+        matches!(
+            self,
+            ExportFormat::Dot | ExportFormat::Json | ExportFormat::Mermaid | ExportFormat::Xml
+        )
+    }
+}
+
 /// Export the tree to a file. Writes to a custom filename if specified, otherwise
 /// the filename corresponds to the tree mode (normal, filetype, or Git status)
 /// and the current timestamp.
+///
+/// DOT, JSON, Mermaid, and XML exports are built from the `JsonItem`, so it has
+/// to be provided for those formats.
 pub fn export_tree(
     config: PrintConfig,
+    export_format: ExportFormat,
     export_mode: ExportMode,
     filename: &Option<String>,
+    graph_args: &GraphArgs,
+    json_item: Option<JsonItem>,
+    nomad_style: &NomadStyle,
     tree: StringItem,
 ) -> Result<(), NomadError> {
     let mut file_header = "nomad".to_string();
+    let mut json_export = JsonExport {
+        mode: "normal".to_string(),
+        filetypes: None,
+        globs: None,
+        tree: None,
+    };
 
     let mut default_filename = match export_mode {
         ExportMode::Filetype(filetypes, globs) => {
@@ -56,6 +130,10 @@ pub fn export_tree(
 
             file_header.push_str(&filetype_info);
 
+            json_export.mode = "filetype".to_string();
+            json_export.filetypes = Some(filetypes.clone());
+            json_export.globs = Some(globs.clone());
+
             "filetype".to_string()
         }
         ExportMode::Normal => {
@@ -66,11 +144,29 @@ pub fn export_tree(
         ExportMode::GitBranch => {
             file_header.push_str("\n\nMode: Git branch\n\n");
 
+            json_export.mode = "git_branch".to_string();
+
             "git_branch".to_string()
         }
+        ExportMode::GitChurn => {
+            file_header.push_str("\n\nMode: Git churn\n\n");
+
+            json_export.mode = "git_churn".to_string();
+
+            "git_churn".to_string()
+        }
+        ExportMode::GitOwners => {
+            file_header.push_str("\n\nMode: Git owners\n\n");
+
+            json_export.mode = "git_owners".to_string();
+
+            "git_owners".to_string()
+        }
         ExportMode::GitStatus => {
             file_header.push_str("\n\nMode: Git status\n\n");
 
+            json_export.mode = "git_status".to_string();
+
             "git_status".to_string()
         }
     };
@@ -79,29 +175,361 @@ pub fn export_tree(
         filename.to_string()
     } else {
         let timestamp = Local::now().format("%F_%H-%M-%S").to_string();
-        default_filename.push_str(&format!("_{}.txt", timestamp));
+        let extension = match export_format {
+            ExportFormat::Dot => "dot",
+            ExportFormat::Html => "html",
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Mermaid => "mmd",
+            ExportFormat::Text => "txt",
+            ExportFormat::Xml => "xml",
+        };
+        default_filename.push_str(&format!("_{timestamp}.{extension}"));
 
         default_filename
     };
 
     let file_path = get_absolute_path(&export_filename)?;
+
+    let contents = match export_format {
+        ExportFormat::Dot => get_dot_export(
+            &file_header,
+            graph_args,
+            &json_item.ok_or(NomadError::UnsupportedExport)?,
+            nomad_style,
+        ),
+        ExportFormat::Html => get_html_export(&file_header, &tree),
+        ExportFormat::Json => {
+            json_export.tree = Some(json_item.ok_or(NomadError::UnsupportedExport)?);
+
+            serde_json::to_string_pretty(&json_export)?
+        }
+        ExportFormat::Markdown => format!(
+            "{}```\n{}```\n",
+            file_header,
+            get_plain_tree(&config, &file_path, &tree)?
+        ),
+        ExportFormat::Mermaid => get_mermaid_export(
+            &file_header,
+            graph_args,
+            &json_item.ok_or(NomadError::UnsupportedExport)?,
+            nomad_style,
+        ),
+        ExportFormat::Text => format!(
+            "{}{}",
+            file_header,
+            get_plain_tree(&config, &file_path, &tree)?
+        ),
+        ExportFormat::Xml => get_xml_export(
+            &file_header,
+            &json_item.ok_or(NomadError::UnsupportedExport)?,
+        ),
+    };
+
     let mut file = File::create(&file_path)?;
-    write!(file, "{}", file_header)?;
+    file.write_all(contents.as_bytes())?;
 
-    write_tree_with(&tree, file, &config).map_or_else(
+    let success_message = Colour::Green
+        .bold()
+        .paint(format!("Tree was exported to {file_path}\n"));
+    println!("{success_message}");
+
+    Ok(())
+}
+
+/// Write the tree to a `String` without any colors.
+fn get_plain_tree(
+    config: &PrintConfig,
+    file_path: &str,
+    tree: &StringItem,
+) -> Result<String, NomadError> {
+    let mut buffer = Vec::new();
+
+    write_tree_with(tree, &mut buffer, config).map_or_else(
         |error| {
             Err(NomadError::PTreeError {
                 context: format!("Unable to export directory tree to {file_path}"),
                 source: error,
             })
         },
-        |_| {
-            let success_message = Colour::Green
-                .bold()
-                .paint(format!("Tree was exported to {file_path}\n"));
-            println!("{success_message}");
-
-            Ok(())
-        },
+        |_| Ok(strip_ansi(&String::from_utf8_lossy(&buffer))),
     )
 }
+
+/// Remove all ANSI escape sequences from the text.
+fn strip_ansi(text: &str) -> String {
+    match Regex::new(r"\x1b\[[0-9;]*m") {
+        Ok(regex) => regex.replace_all(text, "").to_string(),
+        Err(_) => text.to_string(),
+    }
+}
+
+/// Escape characters that have special meanings in HTML and XML.
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Build an HTML document containing the colored tree. Each item containing
+/// children is wrapped in a collapsible `<details>` element.
+fn get_html_export(file_header: &str, tree: &StringItem) -> String {
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>nomad</title>
+<style>
+body {{ background-color: #1c1c1c; color: #d0d0d0; font-family: "Hack Nerd Font", monospace; }}
+details, .item {{ margin-left: 1.5em; white-space: pre; }}
+.tree > details {{ margin-left: 0; }}
+summary {{ cursor: pointer; }}
+</style>
+</head>
+<body>
+<pre>{}</pre>
+<div class="tree">
+"#,
+        escape_markup(file_header.trim_end())
+    );
+
+    write_html_item(tree, &mut html);
+
+    html.push_str("</div>\n</body>\n</html>\n");
+
+    html
+}
+
+/// Recursively write an item and its children to the HTML document.
+fn write_html_item(item: &StringItem, html: &mut String) {
+    if item.children.is_empty() {
+        html.push_str(&format!(
+            "<div class=\"item\">{}</div>\n",
+            ansi_to_html(&item.text)
+        ));
+    } else {
+        html.push_str(&format!(
+            "<details open>\n<summary>{}</summary>\n",
+            ansi_to_html(&item.text)
+        ));
+
+        for child in item.children.iter() {
+            write_html_item(child, html);
+        }
+
+        html.push_str("</details>\n");
+    }
+}
+
+/// Convert text containing ANSI escape sequences into HTML `<span>`s.
+fn ansi_to_html(text: &str) -> String {
+    let mut html = String::new();
+    let mut style = Style::new();
+    let mut span_is_open = false;
+
+    let mut characters = text.chars().peekable();
+    while let Some(character) = characters.next() {
+        if character == '\x1b' && characters.peek() == Some(&'[') {
+            characters.next();
+
+            let mut parameters = String::new();
+            for parameter in characters.by_ref() {
+                if parameter == 'm' {
+                    break;
+                }
+
+                parameters.push(parameter);
+            }
+
+            style = apply_sgr_parameters(style, &parameters);
+
+            if span_is_open {
+                html.push_str("</span>");
+                span_is_open = false;
+            }
+            if style != Style::new() {
+                html.push_str(&format!("<span style=\"{}\">", style_to_css(&style)));
+                span_is_open = true;
+            }
+        } else {
+            html.push_str(&escape_markup(&character.to_string()));
+        }
+    }
+
+    if span_is_open {
+        html.push_str("</span>");
+    }
+
+    html
+}
+
+/// Apply the parameters of an ANSI SGR (Select Graphic Rendition) sequence to the style.
+fn apply_sgr_parameters(mut style: Style, parameters: &str) -> Style {
+    let codes = parameters
+        .split(';')
+        .map(|code| code.parse::<u8>().unwrap_or(0))
+        .collect::<Vec<u8>>();
+
+    let mut index = 0;
+    while index < codes.len() {
+        match codes[index] {
+            0 => style = Style::new(),
+            1 => style.is_bold = true,
+            2 => style.is_dimmed = true,
+            3 => style.is_italic = true,
+            4 => style.is_underline = true,
+            9 => style.is_strikethrough = true,
+            22 => {
+                style.is_bold = false;
+                style.is_dimmed = false;
+            }
+            23 => style.is_italic = false,
+            24 => style.is_underline = false,
+            29 => style.is_strikethrough = false,
+            code @ 30..=37 => style.foreground = Some(Colour::Fixed(code - 30)),
+            code @ 40..=47 => style.background = Some(Colour::Fixed(code - 40)),
+            code @ 90..=97 => style.foreground = Some(Colour::Fixed(code - 90 + 8)),
+            code @ 100..=107 => style.background = Some(Colour::Fixed(code - 100 + 8)),
+            39 => style.foreground = None,
+            49 => style.background = None,
+            code @ (38 | 48) => {
+                let colour = match codes.get(index + 1) {
+                    Some(5) => {
+                        index += 2;
+                        codes.get(index).map(|color| Colour::Fixed(*color))
+                    }
+                    Some(2) => {
+                        index += 4;
+                        match (codes.get(index - 2), codes.get(index - 1), codes.get(index)) {
+                            (Some(r), Some(g), Some(b)) => Some(Colour::RGB(*r, *g, *b)),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+
+                if code == 38 {
+                    style.foreground = colour;
+                } else {
+                    style.background = colour;
+                }
+            }
+            _ => {}
+        }
+
+        index += 1;
+    }
+
+    style
+}
+
+/// Convert an `ansi_term::Style` into inline CSS.
+fn style_to_css(style: &Style) -> String {
+    let mut css = Vec::new();
+
+    if let Some(colour) = style.foreground {
+        css.push(format!("color: {}", convert_ansi_to_hex(colour)));
+    }
+    if let Some(colour) = style.background {
+        css.push(format!("background-color: {}", convert_ansi_to_hex(colour)));
+    }
+    if style.is_bold {
+        css.push("font-weight: bold".to_string());
+    }
+    if style.is_dimmed {
+        css.push("opacity: 0.7".to_string());
+    }
+    if style.is_italic {
+        css.push("font-style: italic".to_string());
+    }
+    if style.is_underline || style.is_strikethrough {
+        let mut lines = Vec::new();
+        if style.is_underline {
+            lines.push("underline");
+        }
+        if style.is_strikethrough {
+            lines.push("line-through");
+        }
+
+        css.push(format!("text-decoration: {}", lines.join(" ")));
+    }
+
+    css.join("; ")
+}
+
+/// Build an XML document that is compatible with the output of `tree -X`.
+fn get_xml_export(file_header: &str, json_item: &JsonItem) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!--\n{}\n-->\n<tree>\n",
+        file_header.trim_end().replace("--", "- -")
+    );
+
+    let (mut num_directories, mut num_files) = (0, 0);
+    write_xml_item(
+        json_item,
+        &json_item.path,
+        &mut xml,
+        1,
+        &mut num_directories,
+        &mut num_files,
+    );
+
+    xml.push_str(&format!(
+        "  <report>\n    <directories>{num_directories}</directories>\n    <files>{num_files}</files>\n  </report>\n</tree>\n"
+    ));
+
+    xml
+}
+
+/// Recursively write an item and its children to the XML document.
+///
+/// The root directory is not included in the number of directories, which
+/// matches the report displayed by `tree`.
+fn write_xml_item(
+    json_item: &JsonItem,
+    name: &str,
+    xml: &mut String,
+    depth: usize,
+    num_directories: &mut usize,
+    num_files: &mut usize,
+) {
+    let indent = "  ".repeat(depth);
+
+    match json_item.item_type {
+        JsonItemType::Directory => {
+            if depth > 1 {
+                *num_directories += 1;
+            }
+
+            xml.push_str(&format!(
+                "{indent}<directory name=\"{}\">\n",
+                escape_markup(name)
+            ));
+
+            if let Some(ref children) = json_item.children {
+                for child in children.iter() {
+                    write_xml_item(
+                        child,
+                        &child.name,
+                        xml,
+                        depth + 1,
+                        num_directories,
+                        num_files,
+                    );
+                }
+            }
+
+            xml.push_str(&format!("{indent}</directory>\n"));
+        }
+        JsonItemType::File => {
+            *num_files += 1;
+
+            xml.push_str(&format!(
+                "{indent}<file name=\"{}\"></file>\n",
+                escape_markup(name)
+            ));
+        }
+    }
+}
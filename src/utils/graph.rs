@@ -0,0 +1,253 @@
+//! Build Graphviz and Mermaid diagrams from a tree.
+
+use crate::{
+    cli::global::GraphArgs,
+    git::markers::get_status_style,
+    models::{JsonItem, JsonItemType},
+    style::models::NomadStyle,
+    utils::paint::convert_ansi_to_hex,
+};
+
+use std::collections::VecDeque;
+
+/// The maximum number of items that are included in a diagram if a limit is not set.
+const DEFAULT_MAX_NODES: usize = 200;
+
+/// Contains an item that is drawn in a diagram.
+struct GraphNode {
+    /// The hex color code for the node's border and text.
+    color: Option<String>,
+    /// Indicates whether this node is a directory.
+    is_dir: bool,
+    /// Indicates whether this node stands in for items that were left out.
+    is_placeholder: bool,
+    /// Each line of text displayed in the node.
+    label: Vec<String>,
+    /// The index of the node's parent.
+    parent: Option<usize>,
+}
+
+/// Build a Graphviz (DOT) diagram from the tree.
+pub fn get_dot_export(
+    file_header: &str,
+    graph_args: &GraphArgs,
+    json_item: &JsonItem,
+    nomad_style: &NomadStyle,
+) -> String {
+    let mut dot = file_header
+        .trim_end()
+        .lines()
+        .map(|line| format!("{}\n", format!("// {line}").trim_end()))
+        .collect::<String>();
+
+    dot.push_str(
+        "\ndigraph nomad {\n    rankdir=LR;\n    node [fontname=\"monospace\", shape=box];\n\n",
+    );
+
+    let nodes = get_graph_nodes(graph_args, json_item, nomad_style);
+
+    for (id, node) in nodes.iter().enumerate() {
+        let mut attributes = vec![format!(
+            "label=\"{}\"",
+            node.label
+                .iter()
+                .map(|line| line.replace('\\', "\\\\").replace('"', "\\\""))
+                .collect::<Vec<String>>()
+                .join("\\n")
+        )];
+
+        if node.is_dir {
+            attributes.push("shape=folder".to_string());
+        } else if node.is_placeholder {
+            attributes.push("style=dashed".to_string());
+        }
+        if let Some(ref color) = node.color {
+            attributes.push(format!("color=\"{color}\", fontcolor=\"{color}\""));
+        }
+
+        dot.push_str(&format!("    n{id} [{}];\n", attributes.join(", ")));
+    }
+
+    dot.push('\n');
+
+    for (id, node) in nodes.iter().enumerate() {
+        if let Some(parent) = node.parent {
+            dot.push_str(&format!("    n{parent} -> n{id};\n"));
+        }
+    }
+
+    dot.push_str("}\n");
+
+    dot
+}
+
+/// Build a Mermaid flowchart from the tree.
+pub fn get_mermaid_export(
+    file_header: &str,
+    graph_args: &GraphArgs,
+    json_item: &JsonItem,
+    nomad_style: &NomadStyle,
+) -> String {
+    let mut mermaid = file_header
+        .trim_end()
+        .lines()
+        .map(|line| format!("{}\n", format!("%% {line}").trim_end()))
+        .collect::<String>();
+
+    mermaid.push_str("flowchart LR\n");
+
+    let nodes = get_graph_nodes(graph_args, json_item, nomad_style);
+
+    for (id, node) in nodes.iter().enumerate() {
+        let label = node
+            .label
+            .iter()
+            .map(|line| {
+                line.replace('"', "#quot;")
+                    .replace('<', "#lt;")
+                    .replace('>', "#gt;")
+            })
+            .collect::<Vec<String>>()
+            .join("<br/>");
+
+        mermaid.push_str(&if node.is_dir {
+            format!("    n{id}[[\"{label}\"]]\n")
+        } else if node.is_placeholder {
+            format!("    n{id}([\"{label}\"])\n")
+        } else {
+            format!("    n{id}[\"{label}\"]\n")
+        });
+    }
+
+    for (id, node) in nodes.iter().enumerate() {
+        if let Some(parent) = node.parent {
+            mermaid.push_str(&format!("    n{parent} --> n{id}\n"));
+        }
+    }
+
+    for (id, node) in nodes.iter().enumerate() {
+        if let Some(ref color) = node.color {
+            mermaid.push_str(&format!("    style n{id} stroke:{color},color:{color}\n"));
+        } else if node.is_placeholder {
+            mermaid.push_str(&format!("    style n{id} stroke-dasharray: 5 5\n"));
+        }
+    }
+
+    mermaid
+}
+
+/// Convert the tree into a list of nodes.
+///
+/// Items are visited breadth-first so that the top levels of the tree are kept
+/// when the maximum number of nodes is reached. Items that are left out, either
+/// because of the node limit or the depth cut-off, are summarized in a placeholder
+/// node within their parent directory.
+fn get_graph_nodes(
+    graph_args: &GraphArgs,
+    json_item: &JsonItem,
+    nomad_style: &NomadStyle,
+) -> Vec<GraphNode> {
+    let max_nodes = graph_args
+        .graph_max_nodes
+        .unwrap_or(DEFAULT_MAX_NODES)
+        .max(1);
+
+    let mut nodes = vec![get_graph_node(json_item, nomad_style, None)];
+    let mut num_items = 1;
+
+    let mut queue = VecDeque::from([(json_item, 0)]);
+    while let Some((item, id)) = queue.pop_front() {
+        let children = match item.children {
+            Some(ref children) if !children.is_empty() => children,
+            _ => continue,
+        };
+
+        let mut num_hidden = 0;
+
+        if matches!(graph_args.graph_depth, Some(depth) if item.depth as usize >= depth) {
+            num_hidden = children.iter().map(count_items).sum();
+        } else {
+            for child in children.iter() {
+                if num_items < max_nodes {
+                    nodes.push(get_graph_node(child, nomad_style, Some(id)));
+                    queue.push_back((child, nodes.len() - 1));
+
+                    num_items += 1;
+                } else {
+                    num_hidden += count_items(child);
+                }
+            }
+        }
+
+        if num_hidden > 0 {
+            nodes.push(GraphNode {
+                color: None,
+                is_dir: false,
+                is_placeholder: true,
+                label: vec![format!(
+                    "… {num_hidden} more item{}",
+                    if num_hidden > 1 { "s" } else { "" }
+                )],
+                parent: Some(id),
+            });
+        }
+    }
+
+    nodes
+}
+
+/// Create a node for the item. Directories are colored with the directory color
+/// and files are colored with the color of their Git status.
+fn get_graph_node(
+    json_item: &JsonItem,
+    nomad_style: &NomadStyle,
+    parent: Option<usize>,
+) -> GraphNode {
+    let mut label = vec![json_item.name.clone()];
+    if let Some(lines) = count_lines(json_item) {
+        label.push(format!("{lines} lines"));
+    }
+
+    let (color, is_dir) = match json_item.item_type {
+        JsonItemType::Directory => (
+            nomad_style.tree.item_colors.directory_color.foreground,
+            true,
+        ),
+        JsonItemType::File => (
+            json_item
+                .git_status
+                .and_then(|git_status| get_status_style(nomad_style, git_status).0.foreground),
+            false,
+        ),
+    };
+
+    GraphNode {
+        color: color.map(convert_ansi_to_hex),
+        is_dir,
+        is_placeholder: false,
+        label,
+        parent,
+    }
+}
+
+/// Count the item and every item nested within it.
+fn count_items(json_item: &JsonItem) -> usize {
+    1 + json_item
+        .children
+        .as_ref()
+        .map_or(0, |children| children.iter().map(count_items).sum())
+}
+
+/// Count the lines in a file, or the total lines of every file within a directory.
+/// Returns `None` if `tokei` stats were not collected.
+fn count_lines(json_item: &JsonItem) -> Option<usize> {
+    match json_item.item_type {
+        JsonItemType::Directory => json_item
+            .children
+            .as_ref()?
+            .iter()
+            .filter_map(count_lines)
+            .reduce(|total, lines| total + lines),
+        JsonItemType::File => json_item.tokei.as_ref().map(|tokei| tokei.lines),
+    }
+}
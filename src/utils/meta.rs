@@ -1,13 +1,13 @@
 //! Retrieving metadata for files.
 
-use crate::cli::global::GlobalArgs;
+use crate::{cli::global::GlobalArgs, models::JsonMetadata};
 
 use ansi_term::Colour;
 use chrono::{Local, NaiveDateTime};
 use unix_mode::to_string;
 use users::{get_group_by_gid, get_user_by_uid};
 
-use std::path::Path;
+use std::{fs::Metadata, path::Path, time::UNIX_EPOCH};
 
 #[cfg(target_family = "unix")]
 use std::os::unix::fs::{MetadataExt, PermissionsExt};
@@ -27,11 +27,41 @@ pub fn convert_time(timestamp: i64) -> String {
     }
 }
 
+/// Convert a UNIX timestamp to a time relative to now, ie. `3 days ago`.
+///
+/// Timestamps that are more than a year old or in the future are converted to a
+/// readable date instead.
+pub fn convert_relative_time(timestamp: i64) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    const WEEK: i64 = 7 * DAY;
+    const MONTH: i64 = 30 * DAY;
+    const YEAR: i64 = 365 * DAY;
+
+    let elapsed = Local::now().timestamp() - timestamp;
+
+    let (amount, unit) = match elapsed {
+        _ if !(0..YEAR).contains(&elapsed) => return convert_time(timestamp),
+        _ if elapsed < MINUTE => return "just now".to_string(),
+        _ if elapsed < HOUR => (elapsed / MINUTE, "minute"),
+        _ if elapsed < DAY => (elapsed / HOUR, "hour"),
+        _ if elapsed < WEEK => (elapsed / DAY, "day"),
+        _ if elapsed < MONTH => (elapsed / WEEK, "week"),
+        _ => (elapsed / MONTH, "month"),
+    };
+
+    format!(
+        "{amount} {unit}{plurality} ago",
+        plurality = if amount > 1 { "s" } else { "" }
+    )
+}
+
 /// Convert bytes to different units depending on size.
 ///
 /// Petabyte is the largest unit of data that may be converted. Otherwise, file
 /// sizes will be displayed in bytes.
-fn convert_bytes(bytes: i64) -> String {
+pub fn convert_bytes(bytes: i64) -> String {
     let (convert_by, label): (i64, &str) = match bytes {
         1000..=999999 => (1000, "KB"),
         1000000..=9999999 => (1000000, "MB"),
@@ -222,3 +252,69 @@ pub fn get_metadata(args: &GlobalArgs, item: &Path) -> String {
             .to_string()
     }
 }
+
+/// Get the number of bytes an item takes up on disk.
+///
+/// This is only compiled when on UNIX systems.
+#[cfg(target_family = "unix")]
+pub fn get_disk_size(metadata: &Metadata) -> u64 {
+    metadata.blocks() * 512
+}
+
+/// Get the number of bytes an item takes up on disk. Windows does not expose the
+/// allocation size, so this falls back to the apparent size.
+///
+/// This is only compiled when on Windows systems.
+#[cfg(target_family = "windows")]
+pub fn get_disk_size(metadata: &Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Get the time an item's metadata was last changed as a UNIX timestamp.
+///
+/// This is only compiled when on UNIX systems.
+#[cfg(target_family = "unix")]
+pub fn get_changed_time(metadata: &Metadata) -> Option<u64> {
+    u64::try_from(metadata.ctime()).ok()
+}
+
+/// Get the time an item was created as a UNIX timestamp. Windows does not track
+/// when an item's metadata was last changed.
+///
+/// This is only compiled when on Windows systems.
+#[cfg(target_family = "windows")]
+pub fn get_changed_time(metadata: &Metadata) -> Option<u64> {
+    metadata
+        .created()
+        .ok()
+        .and_then(|created| created.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+}
+
+/// Get the metadata for a directory or file in a form that may be serialized to JSON.
+pub fn get_json_metadata(item: &Path) -> Option<JsonMetadata> {
+    let metadata = item.metadata().ok()?;
+
+    #[cfg(target_family = "unix")]
+    let (group, mode, user) = (
+        get_group_by_gid(metadata.gid())
+            .and_then(|group| group.name().to_str().map(|name| name.to_string())),
+        Some(to_string(metadata.permissions().mode())),
+        get_user_by_uid(metadata.uid())
+            .and_then(|user| user.name().to_str().map(|name| name.to_string())),
+    );
+    #[cfg(target_family = "windows")]
+    let (group, mode, user) = (None, None, None);
+
+    Some(JsonMetadata {
+        group,
+        last_modified: metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs()),
+        mode,
+        size: metadata.len(),
+        user,
+    })
+}
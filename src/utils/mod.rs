@@ -3,6 +3,7 @@
 pub mod bat;
 pub mod cache;
 pub mod export;
+pub mod graph;
 pub mod icons;
 pub mod meta;
 pub mod open;
@@ -7,15 +7,21 @@ use anyhow::{anyhow, Result};
 use serde_json::{self, from_str};
 
 use std::{
+    collections::HashMap,
     env::var,
-    io::Read,
+    io::{ErrorKind, Read},
+    path::Path,
     process::{Command, ExitStatus},
 };
 
 /// Open the target file with an editor.
-fn spawn_editor(editor: String, found_items: Vec<String>) -> Result<ExitStatus, NomadError> {
+fn spawn_editor(
+    editor: String,
+    found_items: Vec<String>,
+    match_positions: &HashMap<String, (usize, usize)>,
+) -> Result<ExitStatus, NomadError> {
     Command::new(editor.clone())
-        .args(&found_items)
+        .args(get_editor_args(&editor, found_items, match_positions))
         .status()
         .map_err(|error| NomadError::EditorError {
             editor,
@@ -37,6 +43,78 @@ fn get_text_editors() -> Vec<String> {
     )
 }
 
+/// Get the arguments passed into the editor. If the first file was found with
+/// `--grep`, the editor is told to open it at the first match.
+///
+/// Editors that do not have a known way to jump to a column are opened at the
+/// line instead.
+fn get_editor_args(
+    editor: &str,
+    found_items: Vec<String>,
+    match_positions: &HashMap<String, (usize, usize)>,
+) -> Vec<String> {
+    let (line, column) = match found_items
+        .first()
+        .and_then(|first_item| match_positions.get(first_item))
+    {
+        Some(position) => *position,
+        None => return found_items,
+    };
+
+    let mut editor_args = match Path::new(editor)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(editor)
+    {
+        "code" | "codium" => {
+            let mut editor_args = vec![
+                "--goto".to_string(),
+                format!("{}:{line}:{column}", found_items[0]),
+            ];
+            editor_args.extend(found_items.into_iter().skip(1));
+
+            return editor_args;
+        }
+        "hx" | "subl" => {
+            let mut editor_args = vec![format!("{}:{line}:{column}", found_items[0])];
+            editor_args.extend(found_items.into_iter().skip(1));
+
+            return editor_args;
+        }
+        "nvim" | "vim" => vec![format!("+call cursor({line}, {column})")],
+        "nano" => vec![format!("+{line},{column}")],
+        "emacs" | "micro" => vec![format!("+{line}:{column}")],
+        _ => vec![format!("+{line}")],
+    };
+    editor_args.extend(found_items);
+
+    editor_args
+}
+
+/// Open a single file with the first available editor and wait until it is closed.
+/// Returns an error if the editor exits with a failure.
+pub fn edit_file(file_path: &Path) -> Result<(), NomadError> {
+    for editor in get_text_editors() {
+        match Command::new(&editor).arg(file_path).status() {
+            Ok(status) if status.success() => return Ok(()),
+            Ok(status) => {
+                return Err(NomadError::Error(anyhow!(
+                    "{editor} exited with a failure ({status})!"
+                )))
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => continue,
+            Err(error) => {
+                return Err(NomadError::EditorError {
+                    editor,
+                    reason: error,
+                })
+            }
+        }
+    }
+
+    Err(NomadError::Error(anyhow!("Could not open the file with your $EDITOR, Neovim, Vim, Vi, or Nano!\nDo you have one of these editors installed?")))
+}
+
 /// Get the deserialized JSON file.
 pub fn get_deserialized_json() -> Result<Contents, NomadError> {
     let mut file = get_json_file(true)?;
@@ -49,16 +127,22 @@ pub fn get_deserialized_json() -> Result<Contents, NomadError> {
 /// Open the target file.
 pub fn open_files(found_items: Vec<String>) -> Result<(), NomadError> {
     let editors = get_text_editors();
+    let match_positions = get_deserialized_json()
+        .map(|contents| contents.positions)
+        .unwrap_or_default();
 
     if editors.len() == 1 {
-        spawn_editor(editors[0].to_string(), found_items).map_or_else(Err, |status_code| {
-            println!("{status_code}");
+        spawn_editor(editors[0].to_string(), found_items, &match_positions).map_or_else(
+            Err,
+            |status_code| {
+                println!("{status_code}");
 
-            Ok(())
-        })
+                Ok(())
+            },
+        )
     } else {
         for editor in editors {
-            if spawn_editor(editor, found_items.clone()).is_ok() {
+            if spawn_editor(editor, found_items.clone(), &match_positions).is_ok() {
                 return Ok(());
             };
         }
@@ -30,6 +30,46 @@ pub fn show_banner() {
     );
 }
 
+/// Converts an `ansi_term::Colour` to a hex color code.
+pub fn convert_ansi_to_hex(colour: Colour) -> String {
+    let index = match colour {
+        Colour::Black => 0,
+        Colour::Red => 1,
+        Colour::Green => 2,
+        Colour::Yellow => 3,
+        Colour::Blue => 4,
+        Colour::Purple => 5,
+        Colour::Cyan => 6,
+        Colour::White => 7,
+        Colour::Fixed(index) => index,
+        Colour::RGB(r, g, b) => return format!("#{r:02x}{g:02x}{b:02x}"),
+    };
+
+    match index {
+        0..=15 => [
+            "#000000", "#800000", "#008000", "#808000", "#000080", "#800080", "#008080", "#c0c0c0",
+            "#808080", "#ff0000", "#00ff00", "#ffff00", "#0000ff", "#ff00ff", "#00ffff", "#ffffff",
+        ][index as usize]
+            .to_string(),
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let cube_index = index - 16;
+
+            format!(
+                "#{:02x}{:02x}{:02x}",
+                level(cube_index / 36),
+                level((cube_index % 36) / 6),
+                level(cube_index % 6)
+            )
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+
+            format!("#{gray:02x}{gray:02x}{gray:02x}")
+        }
+    }
+}
+
 /// Converts an `ansi_term::Colour` to a `syntect::highlighting::Color`
 pub fn convert_ansi_to_syntect(color: u8) -> Color {
     match color {
@@ -8,7 +8,7 @@ use std::{
     env,
     ffi::OsStr,
     fs::read_link,
-    path::{Path, PathBuf},
+    path::{is_separator, Path, PathBuf},
 };
 
 /// Get the current directory.
@@ -44,6 +44,18 @@ pub fn get_filename(item: &Path) -> String {
         .to_string()
 }
 
+/// Get the last components of a path as a `String`, ie. `java/com/acme` if three
+/// components are requested for `src/main/java/com/acme`.
+pub fn get_last_components(path: &str, num_components: usize) -> String {
+    match path
+        .rmatch_indices(is_separator)
+        .nth(num_components.saturating_sub(1))
+    {
+        Some((index, _)) => path[index + 1..].to_string(),
+        None => path.to_string(),
+    }
+}
+
 /// Get the symlinked item.
 pub fn get_symlink(item: &Path) -> String {
     let points_to = read_link(item).map_or("?".to_string(), |pathbuf_path| {